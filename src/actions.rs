@@ -1,4 +1,5 @@
 use crate::{models::FolderInfo, ui::app::AppMode};
+use std::path::PathBuf;
 
 #[derive(Clone, PartialEq, Eq)]
 pub enum AppAction {
@@ -6,12 +7,33 @@ pub enum AppAction {
     Quit,
     Tick,
     SwitchMode(AppMode),
+    CycleDateFormat,
     ArtifactsSortByPath,
     ArtifactsSortBySize,
     ArtifactsSortByLastMod,
+    ArtifactsSortByStaleness,
+    ArtifactsToggleRecommended,
     ArtifactsRemoveRow,
     ArtifactsInsertRow(FolderInfo),
+    ArtifactsDropRow(PathBuf),
     ArtifactsSelectNextRow,
     ArtifactsSelectPreviousRow,
+    ArtifactsExpandRow,
+    ArtifactsCollapseRow,
+    CachesRemoveRow,
+    CachesInsertRow(FolderInfo),
+    CachesSelectNextRow,
+    CachesSelectPreviousRow,
+    HistorySelectNextRow,
+    HistorySelectPreviousRow,
+    GoalOpen,
+    GoalInput(char),
+    GoalBackspace,
+    GoalSubmit,
+    GoalConfirm,
+    GoalCancel,
+    ProtectedRemovalRequest(Vec<FolderInfo>),
+    ProtectedRemovalConfirm,
+    ProtectedRemovalCancel,
     Error(String),
 }
@@ -0,0 +1,66 @@
+use crate::{config::Config, goal::Goal, logging};
+use clap::{ArgAction, Parser, Subcommand};
+use std::path::PathBuf;
+
+/// Find and remove build artifacts and package manager caches.
+#[derive(Parser, Debug, Default)]
+#[command(version, about)]
+pub struct Cli {
+    /// Do not descend into directories on other filesystems
+    #[arg(short = 'x', long, global = true)]
+    pub one_file_system: bool,
+
+    /// Log more, repeat for even more
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    pub verbose: u8,
+
+    /// Log less, repeat for even less
+    #[arg(short, long, action = ArgAction::Count, global = true, conflicts_with = "verbose")]
+    pub quiet: u8,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Delete artifacts below the current directory until a goal is reached
+    Free {
+        /// Bytes to release, like `50GB`, or free space to reach on a
+        /// filesystem, like `/home=20%` or `/home=100GB`
+        goal: Goal,
+        /// Delete without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// List archived or relocated artifacts, or put one back where it came
+    /// from
+    Restore {
+        /// The original folder, or the archive or folder it was moved to;
+        /// lists everything that can be restored if omitted
+        path: Option<PathBuf>,
+    },
+    /// Show how much space was freed over time
+    History {
+        /// Number of latest removals to list
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+    },
+}
+
+impl Cli {
+    pub fn logging(&self) -> logging::Options {
+        logging::Options {
+            verbosity: i16::from(self.verbose) - i16::from(self.quiet),
+            // Logging to stderr would garble the TUI.
+            stderr: self.command.is_some(),
+        }
+    }
+
+    /// Applies the command line options on top of the loaded config.
+    pub fn apply(&self, config: &mut Config) {
+        if self.one_file_system {
+            config.scan.one_file_system = true;
+        }
+    }
+}
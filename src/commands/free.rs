@@ -0,0 +1,117 @@
+use crate::{
+    config::{Config, GitProtection},
+    enricher::{Enricher, FsEnricher},
+    errors::Result,
+    goal::{Goal, Plan},
+    removal::Removal,
+    utils::{
+        humanize::{DateFormat, format_size},
+        scanner::find_target_dirs,
+    },
+};
+use rayon::prelude::*;
+use std::{
+    collections::HashSet,
+    io::{self, Write},
+    path::Path,
+};
+use tokio::sync::mpsc;
+
+/// Scans the current directory, plans which artifacts to delete to reach
+/// `goal` and deletes them after confirmation.
+pub fn run(config: Config, goal: Goal, yes: bool) -> Result<()> {
+    let root = std::env::current_dir()?;
+    eprintln!("Scanning {} ...", root.display());
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    find_target_dirs(&root, config.clone(), tx);
+    let mut folders = Vec::new();
+    while let Ok(folder) = rx.try_recv() {
+        folders.push(folder);
+    }
+
+    let enricher = FsEnricher::new(&config);
+    folders
+        .par_iter()
+        .for_each(|folder| enricher.enrich(folder));
+
+    let plan = Plan::new(goal, &folders, &config)?;
+    if plan.needed == 0 {
+        println!("Goal already reached: {}", plan.goal);
+        return Ok(());
+    }
+    if plan.folders.is_empty() {
+        println!("Nothing to delete");
+        return Ok(());
+    }
+
+    print_plan(&plan);
+    if !plan.reaches_goal() {
+        println!(
+            "Deleting everything found frees {}, {} short of the goal",
+            format_size(plan.releases),
+            format_size(plan.needed - plan.releases)
+        );
+    }
+
+    let question = format!(
+        "Delete {} folders to free {}?",
+        plan.folders.len(),
+        format_size(plan.releases)
+    );
+    if !yes && !ask(&question)? {
+        return Ok(());
+    }
+
+    // Under `confirm`, protected folders need an explicit answer of their own,
+    // which `--yes` does not give.
+    let protected: HashSet<&Path> = plan
+        .protected()
+        .into_iter()
+        .map(|folder| folder.path.as_path())
+        .collect();
+    let skip_protected = config.protect.git == GitProtection::Confirm
+        && !protected.is_empty()
+        && (yes
+            || !ask(&format!(
+                "{} of them belong to repositories with local work. Delete those too?",
+                protected.len()
+            ))?);
+
+    for folder in &plan.folders {
+        if skip_protected && protected.contains(folder.path.as_path()) {
+            println!("skipped {}", folder.path.display());
+            continue;
+        }
+        match Removal::for_folder(folder, &config).run(folder) {
+            Ok(()) => println!("removed {}", folder.path.display()),
+            Err(err) => eprintln!("failed to remove {}: {err}", folder.path.display()),
+        }
+    }
+    Ok(())
+}
+
+fn print_plan(plan: &Plan) {
+    println!("Plan to {}:", plan.goal);
+    for folder in &plan.folders {
+        println!(
+            "{:>10}  {:>10}  {}",
+            folder.human_exclusive_size().unwrap_or_default(),
+            folder
+                .human_last_modified(DateFormat::Relative)
+                .unwrap_or_default(),
+            folder.path_string()
+        );
+        if let Some(work) = folder.activity().and_then(|activity| activity.local_work()) {
+            println!("{:>24}  [{work}]", "");
+        }
+    }
+}
+
+fn ask(question: &str) -> Result<bool> {
+    print!("{question} [y/N] ");
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
@@ -0,0 +1,73 @@
+use crate::{
+    errors::Result,
+    utils::{
+        history::{self, Outcome, Summary},
+        humanize::format_size,
+    },
+};
+
+/// Prints the lifetime freed space, the totals per ecosystem and the latest
+/// `limit` removals.
+pub fn run(limit: usize) -> Result<()> {
+    let entries = history::load();
+    if entries.is_empty() {
+        println!("Nothing removed yet");
+        return Ok(());
+    }
+
+    let summary = Summary::new(&entries);
+    let since = entries.last().map(|entry| entry.time.format("%Y-%m-%d"));
+    print!(
+        "Freed {} in {} removals",
+        format_size(summary.freed),
+        summary.removals
+    );
+    if summary.unknown_size > 0 {
+        print!(" ({} of unknown size)", summary.unknown_size);
+    }
+    if summary.failures > 0 {
+        print!(" ({} failed)", summary.failures);
+    }
+    if let Some(since) = since {
+        print!(" since {since}");
+    }
+    println!();
+
+    println!("\nBy ecosystem:");
+    for (target, freed, removals) in &summary.targets {
+        println!("{:>10}  {target} ({removals})", format_size(*freed));
+    }
+
+    println!("\nTimeline:");
+    for entry in entries.iter().take(limit) {
+        let (outcome, freed) = match entry.outcome {
+            Outcome::Removed => (
+                "removed",
+                entry
+                    .freed
+                    .map_or_else(|| String::from("unknown"), format_size),
+            ),
+            Outcome::Failed => ("failed", String::new()),
+        };
+        println!(
+            "{}  {outcome:<7}  {freed:>10}  {:<9}  {:<16}  {}",
+            entry.time.format("%Y-%m-%d %H:%M"),
+            entry.strategy,
+            entry.target,
+            entry.path.display()
+        );
+        if let Some(error) = &entry.error {
+            println!("{:18}{error}", "");
+        }
+    }
+    if entries.len() > limit {
+        println!("... {} more, see {}", entries.len() - limit, history_path());
+    }
+    Ok(())
+}
+
+fn history_path() -> String {
+    history::path()
+        .map(|path| path.display().to_string())
+        .unwrap_or_default()
+}
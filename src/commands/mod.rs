@@ -0,0 +1,3 @@
+pub mod free;
+pub mod history;
+pub mod restore;
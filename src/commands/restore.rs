@@ -0,0 +1,97 @@
+use crate::{
+    config::Config,
+    errors::{Error, Result},
+    utils::{
+        archive,
+        humanize::{DateFormat, format_date, format_size},
+        relocate,
+    },
+};
+use std::{
+    fs,
+    path::{self, Path, PathBuf},
+    time::SystemTime,
+};
+
+/// Lists archived and relocated folders without `path`, otherwise puts back
+/// the most recent one of `path`, which is either the original folder or
+/// where it was moved to.
+pub fn run(config: Config, path: Option<PathBuf>) -> Result<()> {
+    let archives = archive::list(&config.archive);
+    let relocations = relocate::list(&config.relocate);
+    let Some(path) = path else {
+        if archives.is_empty() && relocations.is_empty() {
+            println!("Nothing archived or relocated");
+        }
+        for archive in &archives {
+            let manifest = &archive.manifest;
+            print_entry(
+                manifest.archived,
+                manifest.size,
+                &manifest.path,
+                &archive.file,
+            );
+        }
+        for relocation in &relocations {
+            print_entry(
+                relocation.relocated,
+                relocation.size,
+                &relocation.from,
+                &relocation.to,
+            );
+        }
+        return Ok(());
+    };
+
+    let path = resolve(&path)?;
+    let matches = |other: &Path| other == path || resolve(other).is_ok_and(|other| other == path);
+    if let Some(archive) = archives
+        .iter()
+        .find(|archive| matches(&archive.manifest.path) || matches(&archive.file))
+    {
+        archive::restore(archive)?;
+        println!(
+            "restored {} from {}",
+            archive.manifest.path.display(),
+            archive.file.display()
+        );
+    } else if let Some(relocation) = relocations
+        .iter()
+        .find(|relocation| matches(&relocation.from) || matches(&relocation.to))
+    {
+        relocate::restore(relocation, &config.relocate)?;
+        println!(
+            "moved {} back from {}",
+            relocation.from.display(),
+            relocation.to.display()
+        );
+    } else {
+        return Err(Error::Archive(format!(
+            "{} was neither archived nor relocated",
+            path.display()
+        )));
+    }
+    Ok(())
+}
+
+/// `path` made absolute, with `..` and symlinks resolved in its parent. The
+/// last component is kept as is: an archived folder is gone and a relocated
+/// one may be a symlink to where it was moved.
+fn resolve(path: &Path) -> Result<PathBuf> {
+    let path = path::absolute(path)?;
+    let resolved = match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => fs::canonicalize(parent).map(|parent| parent.join(name)),
+        _ => fs::canonicalize(&path),
+    };
+    Ok(resolved.unwrap_or(path))
+}
+
+fn print_entry(time: SystemTime, size: Option<u64>, path: &Path, location: &Path) {
+    println!(
+        "{:>10}  {:>10}  {}  ({})",
+        format_date(time, DateFormat::Relative),
+        size.map(format_size).unwrap_or_default(),
+        path.display(),
+        location.display()
+    );
+}
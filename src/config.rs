@@ -1,33 +1,716 @@
-use crate::models::TargetInfo;
-use color_eyre::Result;
-use serde::Deserialize;
-use std::{fs, path::PathBuf};
+use crate::models::{CacheInfo, TargetInfo, expand_path};
+use color_eyre::{Result, eyre::WrapErr};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+use toml::{Table, Value};
 
 static DEFAULT_CONFIG: &str = include_str!("../config.toml");
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct Config {
+    #[serde(default)]
+    pub scan: ScanConfig,
+    #[serde(default)]
+    pub sizing: SizingConfig,
+    #[serde(default)]
+    pub goal: GoalConfig,
+    #[serde(default)]
+    pub staleness: StalenessConfig,
+    #[serde(default)]
+    pub protect: ProtectConfig,
+    #[serde(default)]
+    pub archive: ArchiveConfig,
+    #[serde(default)]
+    pub relocate: RelocateConfig,
     pub targets: Vec<TargetInfo>,
+    #[serde(default)]
+    pub caches: Vec<CacheInfo>,
 }
 
+#[derive(Debug, Deserialize, Clone)]
+pub struct ScanConfig {
+    #[serde(default)]
+    pub hidden: HiddenDirs,
+    #[serde(default)]
+    pub hidden_allowlist: Vec<String>,
+    #[serde(default = "default_cache")]
+    pub cache: bool,
+    #[serde(default)]
+    pub watch: bool,
+    #[serde(default)]
+    pub one_file_system: bool,
+}
+
+impl Default for ScanConfig {
+    fn default() -> Self {
+        Self {
+            hidden: HiddenDirs::default(),
+            hidden_allowlist: Vec::new(),
+            cache: default_cache(),
+            watch: false,
+            one_file_system: false,
+        }
+    }
+}
+
+fn default_cache() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]
+pub struct SizingConfig {
+    #[serde(default = "default_workers")]
+    pub workers: usize,
+    #[serde(default)]
+    pub lazy: bool,
+    #[serde(default)]
+    pub age: AgeSource,
+}
+
+impl Default for SizingConfig {
+    fn default() -> Self {
+        Self {
+            workers: default_workers(),
+            lazy: false,
+            age: AgeSource::default(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum AgeSource {
+    #[default]
+    Folder,
+    NewestFile,
+    Sources,
+    Git,
+}
+
+fn default_workers() -> usize {
+    4
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+pub struct GoalConfig {
+    #[serde(default)]
+    pub score: ScoreRule,
+}
+
+/// Which folders a goal plan deletes first.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScoreRule {
+    /// Old and large folders first: size weighted by age in days.
+    #[default]
+    AgeSize,
+    /// Largest folders first.
+    Size,
+    /// Least recently modified folders first.
+    Age,
+    /// Highest staleness score first.
+    Staleness,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+pub struct ProtectConfig {
+    #[serde(default)]
+    pub git: GitProtection,
+}
+
+/// How to treat artifacts of repositories with uncommitted changes, stashes
+/// or unpushed commits.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GitProtection {
+    /// Delete them like any other folder.
+    Off,
+    /// Ask once more before deleting them.
+    #[default]
+    Confirm,
+    /// Never delete them.
+    Block,
+}
+
+/// How the artifacts of a target are removed.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Strategy {
+    /// Delete them.
+    Delete,
+    /// Run the target's `clean_command`.
+    Clean,
+    /// Move them into a tarball in the archive directory.
+    Archive,
+    /// Move them to the relocation directory, usually on another disk.
+    Relocate,
+}
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Strategy::Delete => "delete",
+            Strategy::Clean => "clean",
+            Strategy::Archive => "archive",
+            Strategy::Relocate => "relocate",
+        })
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct ArchiveConfig {
+    /// Where archives are written, the user data directory by default.
+    #[serde(default)]
+    pub dir: Option<String>,
+    #[serde(default)]
+    pub compression: Compression,
+}
+
+impl ArchiveConfig {
+    pub fn dir(&self) -> PathBuf {
+        self.dir
+            .as_deref()
+            .and_then(expand_path)
+            .or_else(|| dirs::data_dir().map(|dir| dir.join("vaporz/archive")))
+            .unwrap_or_else(|| PathBuf::from("vaporz-archive"))
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    None,
+    #[default]
+    Gzip,
+}
+
+#[derive(Debug, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct RelocateConfig {
+    /// Where relocated artifacts are moved to.
+    #[serde(default)]
+    pub dir: Option<String>,
+    /// Leave a symlink to the new location behind, so builds keep working.
+    #[serde(default)]
+    pub symlink: bool,
+}
+
+impl RelocateConfig {
+    pub fn dir(&self) -> Option<PathBuf> {
+        self.dir.as_deref().and_then(expand_path)
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]
+pub struct StalenessConfig {
+    /// Days without activity after which a project counts as fully stale.
+    #[serde(default = "default_stale_after_days")]
+    pub stale_after_days: u64,
+    /// Projects active within this many days are never recommended.
+    #[serde(default = "default_fresh_days")]
+    pub fresh_days: u64,
+    /// Lowest score recommended for deletion.
+    #[serde(default = "default_recommend_above")]
+    pub recommend_above: u8,
+}
+
+impl Default for StalenessConfig {
+    fn default() -> Self {
+        Self {
+            stale_after_days: default_stale_after_days(),
+            fresh_days: default_fresh_days(),
+            recommend_above: default_recommend_above(),
+        }
+    }
+}
+
+fn default_stale_after_days() -> u64 {
+    90
+}
+
+fn default_fresh_days() -> u64 {
+    14
+}
+
+fn default_recommend_above() -> u8 {
+    50
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HiddenDirs {
+    #[default]
+    Skip,
+    Allowlist,
+    Include,
+}
+
+impl ScanConfig {
+    pub fn skips_hidden(&self, name: &str) -> bool {
+        match self.hidden {
+            HiddenDirs::Skip => true,
+            HiddenDirs::Allowlist => !self.hidden_allowlist.iter().any(|allowed| allowed == name),
+            HiddenDirs::Include => false,
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        toml::from_str(DEFAULT_CONFIG).expect("embedded config.toml is valid")
+    }
+}
+
+impl Config {
+    pub fn artifact_names(&self) -> impl Iterator<Item = &str> {
+        self.targets
+            .iter()
+            .flat_map(|target| target.artifacts.iter())
+            .map(String::as_str)
+    }
+}
+
+/// Loads the embedded defaults with the system, user and project config
+/// files merged on top, in that order. Command line flags are applied last
+/// by [`crate::cli::Cli::apply`].
+///
+/// Tables are merged key by key. Lists of tables with a `name`, like
+/// `targets` and `caches`, are merged by name: a known name overrides fields
+/// of that entry, a new one is appended and `disabled = true` drops it. Other
+/// lists are replaced, unless written as `{ extend = [...] }`; any list can
+/// be replaced wholesale with `{ replace = [...] }`.
 pub fn load_config() -> Result<Config> {
-    let mut config: Config = toml::from_str(DEFAULT_CONFIG)?;
+    let mut merged: Table = toml::from_str(DEFAULT_CONFIG)?;
+    for (path, layer) in config_layers() {
+        let content = fs::read_to_string(&path)?;
+        let mut table: Table = toml::from_str(&content)
+            .wrap_err_with(|| format!("invalid config {}", path.display()))?;
+        if layer == Layer::Project {
+            restrict_project(&mut table, &path);
+        }
+        log::debug!(path:% = path.display(); "loading config");
+        merge_tables(&mut merged, table);
+    }
+    let mut merged = Value::Table(merged);
+    drop_disabled(&mut merged);
+    Ok(merged.try_into()?)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Layer {
+    System,
+    User,
+    Project,
+}
+
+/// The config files that exist, lowest precedence first.
+fn config_layers() -> Vec<(PathBuf, Layer)> {
+    [
+        (system_config_path(), Layer::System),
+        (user_config_path(), Layer::User),
+        (project_config_path(), Layer::Project),
+    ]
+    .into_iter()
+    .filter_map(|(path, layer)| Some((path?, layer)))
+    .filter(|(path, _)| path.is_file())
+    .collect()
+}
 
+fn system_config_path() -> Option<PathBuf> {
     let app_name = env!("CARGO_PKG_NAME");
+    if cfg!(windows) {
+        std::env::var_os("ProgramData")
+            .map(|dir| PathBuf::from(dir).join(format!("{}/config.toml", app_name)))
+    } else {
+        Some(PathBuf::from(format!("/etc/{}/config.toml", app_name)))
+    }
+}
 
-    let config_path: Option<PathBuf> = if cfg!(target_os = "macos") {
+fn user_config_path() -> Option<PathBuf> {
+    let app_name = env!("CARGO_PKG_NAME");
+    if cfg!(target_os = "macos") {
         dirs::home_dir().map(|home| home.join(format!(".config/{}/config.toml", app_name)))
     } else {
         dirs::config_dir().map(|dir| dir.join(format!("{}/config.toml", app_name)))
-    };
+    }
+}
+
+/// The nearest `.vaporz.toml` in the current directory or one of its parents.
+fn project_config_path() -> Option<PathBuf> {
+    let file_name = format!(".{}.toml", env!("CARGO_PKG_NAME"));
+    let current_dir = std::env::current_dir().ok()?;
+    current_dir
+        .ancestors()
+        .map(|dir| dir.join(&file_name))
+        .find(|path| path.is_file())
+}
 
-    if let Some(config_path) = config_path
-        && config_path.exists()
-    {
-        let content = fs::read_to_string(config_path)?;
-        let user_config: Config = toml::from_str(&content)?;
-        config = user_config;
+/// Settings a project config may change. It comes with whatever repository
+/// is checked out, so it must not get to run commands, widen what is
+/// deleted or where things are moved to, or turn off protection. Targets can
+/// only be disabled, not enabled again.
+const PROJECT_KEYS: &[&str] = &[
+    "scan.hidden",
+    "scan.hidden_allowlist",
+    "sizing.workers",
+    "sizing.lazy",
+    "sizing.age",
+    "goal.score",
+    "staleness.stale_after_days",
+    "staleness.fresh_days",
+    "staleness.recommend_above",
+    "targets.name",
+    "targets.disabled",
+];
+
+/// Drops everything but [`PROJECT_KEYS`] from a project config.
+fn restrict_project(table: &mut Table, path: &Path) {
+    let mut ignored = Vec::new();
+    retain_keys(table, "", &mut ignored);
+    ignored.sort();
+    ignored.dedup();
+    if !ignored.is_empty() {
+        log::warn!(
+            path:% = path.display(),
+            ignored:% = ignored.join(",");
+            "ignoring settings a project config may not change"
+        );
     }
+}
+
+fn retain_keys(table: &mut Table, prefix: &str, ignored: &mut Vec<String>) {
+    let keys = table.keys().cloned().collect::<Vec<_>>();
+    for key in keys {
+        let full = format!("{prefix}{key}");
+        let enables = full == "targets.disabled" && table[&key].as_bool() != Some(true);
+        if PROJECT_KEYS.contains(&full.as_str()) && !enables {
+            continue;
+        }
+        let nested = format!("{full}.");
+        let allowed = PROJECT_KEYS.iter().any(|key| key.starts_with(&nested));
+        match table.get_mut(&key) {
+            Some(Value::Table(inner)) if allowed => retain_keys(inner, &nested, ignored),
+            Some(Value::Array(items)) if allowed => {
+                items.retain(Value::is_table);
+                for item in items.iter_mut().filter_map(Value::as_table_mut) {
+                    retain_keys(item, &nested, ignored);
+                }
+            }
+            _ => {
+                table.remove(&key);
+                ignored.push(full);
+            }
+        }
+    }
+}
+
+fn merge_tables(base: &mut Table, layer: Table) {
+    for (key, value) in layer {
+        match base.get_mut(&key) {
+            Some(current) => merge_value(current, value),
+            None => {
+                base.insert(key, resolve(value));
+            }
+        }
+    }
+}
+
+fn merge_value(base: &mut Value, layer: Value) {
+    match (base, layer) {
+        (Value::Table(base), Value::Table(layer)) => merge_tables(base, layer),
+        (Value::Array(base), Value::Array(items)) if is_named_list(&items) => extend(base, items),
+        (Value::Array(base), Value::Table(mut directive)) if is_list_directive(&directive) => {
+            if let Some(Value::Array(items)) = directive.remove("replace") {
+                base.clear();
+                extend(base, items);
+            }
+            if let Some(Value::Array(items)) = directive.remove("extend") {
+                extend(base, items);
+            }
+        }
+        (base, layer) => *base = resolve(layer),
+    }
+}
+
+/// Appends `items` to `base`, merging entries into the one of the same name
+/// and leaving out values it already has.
+fn extend(base: &mut Vec<Value>, items: Vec<Value>) {
+    for item in items {
+        let existing = name_of(&item)
+            .and_then(|name| base.iter().position(|entry| name_of(entry) == Some(name)));
+        match (existing.and_then(|index| base[index].as_table_mut()), item) {
+            (Some(entry), Value::Table(item)) => merge_tables(entry, item),
+            (_, item) => {
+                if !base.contains(&item) {
+                    base.push(resolve(item));
+                }
+            }
+        }
+    }
+}
+
+/// A value without anything to merge into: list directives become plain
+/// lists.
+fn resolve(value: Value) -> Value {
+    match value {
+        Value::Table(mut table) if is_list_directive(&table) => {
+            let mut items = Vec::new();
+            for key in ["replace", "extend"] {
+                if let Some(Value::Array(values)) = table.remove(key) {
+                    extend(&mut items, values);
+                }
+            }
+            Value::Array(items)
+        }
+        Value::Table(table) => Value::Table(
+            table
+                .into_iter()
+                .map(|(key, value)| (key, resolve(value)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(resolve).collect()),
+        value => value,
+    }
+}
+
+fn is_list_directive(table: &Table) -> bool {
+    !table.is_empty() && table.keys().all(|key| key == "extend" || key == "replace")
+}
+
+fn is_named_list(items: &[Value]) -> bool {
+    !items.is_empty() && items.iter().all(|item| name_of(item).is_some())
+}
+
+fn name_of(value: &Value) -> Option<&str> {
+    value.as_table()?.get("name")?.as_str()
+}
+
+/// Removes entries of lists, like targets and caches, marked
+/// `disabled = true`.
+fn drop_disabled(value: &mut Value) {
+    match value {
+        Value::Table(table) => table.iter_mut().for_each(|(_, value)| drop_disabled(value)),
+        Value::Array(items) => {
+            items.retain(|item| {
+                let disabled = item
+                    .as_table()
+                    .and_then(|table| table.get("disabled"))
+                    .and_then(Value::as_bool);
+                disabled != Some(true)
+            });
+            items.iter_mut().for_each(drop_disabled);
+        }
+        _ => {}
+    }
+}
 
-    Ok(config)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merged(layers: &[&str]) -> Value {
+        let mut merged: Table = toml::from_str(layers[0]).unwrap();
+        for layer in &layers[1..] {
+            merge_tables(&mut merged, toml::from_str(layer).unwrap());
+        }
+        let mut merged = Value::Table(merged);
+        drop_disabled(&mut merged);
+        merged
+    }
+
+    fn names(value: &Value, list: &str) -> Vec<String> {
+        value[list]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["name"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    const BASE: &str = r#"
+        [scan]
+        hidden = "skip"
+        hidden_allowlist = [".config", ".local"]
+
+        [[targets]]
+        name = "Rust"
+        markers = ["Cargo.toml"]
+        artifacts = ["target"]
+
+        [[targets]]
+        name = "Node"
+        markers = ["package.json"]
+        artifacts = ["node_modules"]
+    "#;
+
+    #[test]
+    fn overrides_targets_by_name() {
+        let config = merged(&[
+            BASE,
+            r#"
+            [[targets]]
+            name = "Rust"
+            clean_command = "cargo clean"
+            "#,
+        ]);
+        assert_eq!(names(&config, "targets"), ["Rust", "Node"]);
+        let rust = &config["targets"][0];
+        assert_eq!(rust["clean_command"].as_str(), Some("cargo clean"));
+        assert_eq!(rust["artifacts"][0].as_str(), Some("target"));
+    }
+
+    #[test]
+    fn appends_new_targets() {
+        let config = merged(&[
+            BASE,
+            r#"
+            [[targets]]
+            name = "Make"
+            markers = ["Makefile"]
+            artifacts = ["build"]
+            "#,
+        ]);
+        assert_eq!(names(&config, "targets"), ["Rust", "Node", "Make"]);
+    }
+
+    #[test]
+    fn drops_disabled_targets() {
+        let config = merged(&[
+            BASE,
+            r#"
+            [[targets]]
+            name = "Node"
+            disabled = true
+            "#,
+        ]);
+        assert_eq!(names(&config, "targets"), ["Rust"]);
+    }
+
+    #[test]
+    fn later_layer_enables_a_disabled_target() {
+        let config = merged(&[
+            BASE,
+            "[[targets]]\nname = \"Node\"\ndisabled = true",
+            "[[targets]]\nname = \"Node\"\ndisabled = false",
+        ]);
+        assert_eq!(names(&config, "targets"), ["Rust", "Node"]);
+    }
+
+    #[test]
+    fn replaces_plain_lists() {
+        let config = merged(&[BASE, "[scan]\nhidden_allowlist = [\".cache\"]"]);
+        assert_eq!(
+            config["scan"]["hidden_allowlist"],
+            Value::Array(vec![".cache".into()])
+        );
+        assert_eq!(config["scan"]["hidden"].as_str(), Some("skip"));
+    }
+
+    #[test]
+    fn extends_lists_without_duplicates() {
+        let config = merged(&[
+            BASE,
+            "[scan]\nhidden_allowlist = { extend = [\".local\", \".cache\"] }",
+        ]);
+        assert_eq!(
+            config["scan"]["hidden_allowlist"],
+            Value::Array(vec![".config".into(), ".local".into(), ".cache".into()])
+        );
+    }
+
+    #[test]
+    fn extends_lists_of_a_target() {
+        let config = merged(&[
+            BASE,
+            r#"
+            [[targets]]
+            name = "Rust"
+            artifacts = { extend = [".xwin-cache"] }
+            "#,
+        ]);
+        assert_eq!(
+            config["targets"][0]["artifacts"],
+            Value::Array(vec!["target".into(), ".xwin-cache".into()])
+        );
+    }
+
+    #[test]
+    fn replaces_targets() {
+        let config = merged(&[
+            BASE,
+            r#"
+            [[targets.replace]]
+            name = "Make"
+            markers = ["Makefile"]
+            artifacts = ["build"]
+            "#,
+        ]);
+        assert_eq!(names(&config, "targets"), ["Make"]);
+    }
+
+    #[test]
+    fn resolves_directives_without_a_base() {
+        let config = merged(&[
+            BASE,
+            "[[caches]]\nname = \"npm\"\npaths = { extend = [\"~/.npm\"] }",
+        ]);
+        assert_eq!(
+            config["caches"][0]["paths"],
+            Value::Array(vec!["~/.npm".into()])
+        );
+    }
+
+    #[test]
+    fn restricts_project_configs() {
+        let mut project: Table = toml::from_str(
+            r#"
+            [scan]
+            hidden = "include"
+            one_file_system = true
+
+            [archive]
+            dir = "/tmp"
+
+            [[targets]]
+            name = "Rust"
+            disabled = true
+            clean_command = "rm -rf ~"
+            artifacts = { extend = ["src"] }
+
+            [[targets]]
+            name = "Node"
+            disabled = false
+            "#,
+        )
+        .unwrap();
+        restrict_project(&mut project, Path::new(".vaporz.toml"));
+        let expected: Table = toml::from_str(
+            r#"
+            [scan]
+            hidden = "include"
+
+            [[targets]]
+            name = "Rust"
+            disabled = true
+
+            [[targets]]
+            name = "Node"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(project, expected);
+    }
+
+    #[test]
+    fn merged_defaults_deserialize() {
+        let config = merged(&[
+            DEFAULT_CONFIG,
+            "[[targets]]\nname = \"Unity\"\ndisabled = true",
+        ]);
+        let config: Config = config.try_into().unwrap();
+        assert!(config.targets.iter().all(|target| target.name != "Unity"));
+        assert_eq!(config.targets.len(), Config::default().targets.len() - 1);
+    }
 }
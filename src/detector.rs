@@ -0,0 +1,159 @@
+use crate::{
+    models::TargetInfo,
+    utils::venv::{is_virtualenv, python_version},
+};
+use std::{
+    ffi::OsString,
+    fs::{self, FileType},
+    path::{Path, PathBuf},
+};
+
+#[derive(Clone, Debug)]
+pub struct DirEntry {
+    pub name: OsString,
+    pub file_type: FileType,
+}
+
+/// A directory listing, read once and shared by every detector.
+#[derive(Clone, Debug)]
+pub struct Directory {
+    pub path: PathBuf,
+    pub entries: Vec<DirEntry>,
+}
+
+impl Directory {
+    pub fn read(path: &Path) -> Self {
+        let entries = fs::read_dir(path)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                Some(DirEntry {
+                    file_type: entry.file_type().ok()?,
+                    name: entry.file_name(),
+                })
+            })
+            .collect();
+        Self {
+            path: path.to_path_buf(),
+            entries,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn get(&self, name: &str) -> Option<&DirEntry> {
+        self.entries.iter().find(|entry| entry.name == name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    pub fn has_extension(&self, extension: &str) -> bool {
+        self.entries.iter().any(|entry| {
+            Path::new(&entry.name)
+                .extension()
+                .is_some_and(|ext| ext == extension)
+        })
+    }
+
+    pub fn subdirs(&self) -> impl Iterator<Item = &DirEntry> {
+        self.entries.iter().filter(|entry| entry.file_type.is_dir())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Candidate {
+    pub path: PathBuf,
+    pub target: String,
+    pub details: Option<String>,
+}
+
+impl Candidate {
+    pub fn new(path: PathBuf, target: &str) -> Self {
+        Self {
+            path,
+            target: target.to_string(),
+            details: None,
+        }
+    }
+
+    pub fn details(mut self, details: Option<String>) -> Self {
+        self.details = details;
+        self
+    }
+}
+
+/// Recognizes artifacts while the scanner walks a tree.
+///
+/// `detect` is called once for every directory the scanner visits, possibly
+/// from several threads at once, and returns the artifacts found in it: either
+/// the directory itself or paths below it. The scanner never descends into a
+/// returned candidate.
+pub trait Detector: Send + Sync {
+    fn detect(&self, dir: &Directory) -> Vec<Candidate>;
+
+    /// Called for every directory below one where `detect` found artifacts,
+    /// with artifacts nested deeper in that project, like the `node_modules`
+    /// of a package without a `package.json` of its own. Finds nothing by
+    /// default.
+    fn detect_nested(&self, _dir: &Directory) -> Vec<Candidate> {
+        Vec::new()
+    }
+}
+
+impl Detector for TargetInfo {
+    fn detect(&self, dir: &Directory) -> Vec<Candidate> {
+        if !self.is_project_root(dir) {
+            return Vec::new();
+        }
+        self.artifacts
+            .iter()
+            .filter(|artifact| match dir.get(artifact) {
+                Some(entry) => !entry.file_type.is_symlink(),
+                None => {
+                    artifact.contains('/')
+                        && fs::symlink_metadata(dir.path().join(artifact))
+                            .is_ok_and(|m| !m.file_type().is_symlink())
+                }
+            })
+            .map(|artifact| Candidate::new(dir.path().join(artifact), &self.name))
+            .collect()
+    }
+
+    fn detect_nested(&self, dir: &Directory) -> Vec<Candidate> {
+        self.artifacts
+            .iter()
+            .filter(|artifact| {
+                dir.get(artifact)
+                    .is_some_and(|entry| !entry.file_type.is_symlink())
+            })
+            .map(|artifact| Candidate::new(dir.path().join(artifact), &self.name))
+            .collect()
+    }
+}
+
+pub struct VirtualenvDetector;
+
+impl Detector for VirtualenvDetector {
+    fn detect(&self, dir: &Directory) -> Vec<Candidate> {
+        if !is_virtualenv(dir) {
+            return Vec::new();
+        }
+        let details = python_version(dir.path()).map(|version| format!("python {version}"));
+        vec![Candidate::new(dir.path().to_path_buf(), "Python").details(details)]
+    }
+}
+
+pub fn default_detectors(targets: Vec<TargetInfo>) -> Vec<Box<dyn Detector>> {
+    let mut detectors: Vec<Box<dyn Detector>> = vec![Box::new(VirtualenvDetector)];
+    detectors.extend(
+        targets
+            .into_iter()
+            .map(|target| Box::new(target) as Box<dyn Detector>),
+    );
+    detectors
+}
@@ -0,0 +1,112 @@
+use crate::{
+    config::{AgeSource, Config},
+    models::{Activity, FolderInfo},
+    utils::{
+        fs::{calculate_dir_size, modified, newest_modified},
+        git,
+    },
+};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, Instant, SystemTime},
+};
+
+/// How long a project's git activity is reused by sibling artifacts. Short
+/// enough for watch mode to pick up new commits and edits.
+const ACTIVITY_TTL: Duration = Duration::from_secs(10);
+
+/// Fills in the size, last-modified time and git activity of a [`FolderInfo`].
+///
+/// Enrichment is synchronous and may take long for big folders; run it on
+/// whatever executor fits, e.g. a [`SizeScheduler`](crate::utils::scheduler::SizeScheduler)
+/// or `tokio::task::spawn_blocking`.
+pub trait Enricher: Send + Sync {
+    fn enrich(&self, folder: &FolderInfo);
+}
+
+#[derive(Default)]
+pub struct FsEnricher {
+    age: AgeSource,
+    one_file_system: bool,
+    artifact_names: HashSet<String>,
+    projects: Mutex<HashMap<PathBuf, Option<SystemTime>>>,
+    activities: Mutex<HashMap<PathBuf, (Instant, Activity)>>,
+}
+
+impl FsEnricher {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            age: config.sizing.age,
+            one_file_system: config.scan.one_file_system,
+            artifact_names: config.artifact_names().map(String::from).collect(),
+            projects: Mutex::default(),
+            activities: Mutex::default(),
+        }
+    }
+
+    fn modified(&self, folder: &FolderInfo) -> Option<SystemTime> {
+        match self.age {
+            AgeSource::Folder => modified(&folder.path),
+            AgeSource::NewestFile => folder.dir_size()?.newest_modified,
+            AgeSource::Sources => self.project_modified(&folder.project),
+            AgeSource::Git => {
+                git::last_commit_time(&folder.project).or_else(|| modified(&folder.path))
+            }
+        }
+    }
+
+    fn project_modified(&self, project: &Path) -> Option<SystemTime> {
+        if let Ok(projects) = self.projects.lock()
+            && let Some(modified) = projects.get(project)
+        {
+            return *modified;
+        }
+
+        let skip = |path: &Path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with('.') || self.artifact_names.contains(name))
+        };
+        let modified = newest_modified(project, &skip);
+
+        if let Ok(mut projects) = self.projects.lock() {
+            projects.insert(project.to_path_buf(), modified);
+        }
+        modified
+    }
+
+    fn activity(&self, project: &Path) -> Activity {
+        if let Ok(activities) = self.activities.lock()
+            && let Some((read_at, activity)) = activities.get(project)
+            && read_at.elapsed() < ACTIVITY_TTL
+        {
+            return *activity;
+        }
+
+        let activity = Activity::read(project);
+
+        if let Ok(mut activities) = self.activities.lock() {
+            activities.insert(project.to_path_buf(), (Instant::now(), activity));
+        }
+        activity
+    }
+}
+
+impl Enricher for FsEnricher {
+    fn enrich(&self, folder: &FolderInfo) {
+        if self.age != AgeSource::NewestFile && folder.last_modified().is_none() {
+            folder.set_last_modified(self.modified(folder).unwrap_or_else(SystemTime::now));
+        }
+        if folder.dir_size().is_none() {
+            folder.set_size(calculate_dir_size(&folder.path, self.one_file_system));
+        }
+        if folder.last_modified().is_none() {
+            folder.set_last_modified(self.modified(folder).unwrap_or_else(SystemTime::now));
+        }
+        if folder.activity().is_none() {
+            folder.set_activity(self.activity(&folder.project));
+        }
+    }
+}
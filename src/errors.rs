@@ -7,6 +7,14 @@ pub enum Error {
     Io(#[from] std::io::Error),
     #[error("{0}")]
     LockError(String),
+    #[error("{0}")]
+    Goal(String),
+    #[error("{0}")]
+    Clean(String),
+    #[error("{0}")]
+    Archive(String),
+    #[error("{0}")]
+    Relocate(String),
 
     #[error("{0}")]
     Errrr(#[from] color_eyre::eyre::ErrReport),
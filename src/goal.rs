@@ -0,0 +1,255 @@
+use crate::{
+    config::{Config, GitProtection, ScoreRule, StalenessConfig},
+    errors::{Error, Result},
+    models::{FolderInfo, ProcessStatus},
+    staleness,
+    utils::{
+        fs::releasable_size,
+        humanize::{format_size, parse_size},
+        mounts,
+    },
+};
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fmt,
+    path::PathBuf,
+    str::FromStr,
+    time::{Duration, SystemTime},
+};
+
+/// How much space a deletion plan should free.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Goal {
+    /// Release at least this many bytes, e.g. `50GB`.
+    Release(u64),
+    /// Delete until the filesystem of `path` has this much free space,
+    /// e.g. `/home=20%` or `/home=100GB`.
+    FreeOn { path: PathBuf, free: Amount },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Amount {
+    Bytes(u64),
+    Percent(f64),
+}
+
+impl FromStr for Goal {
+    type Err = String;
+
+    fn from_str(input: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = || format!("invalid goal `{input}`, expected e.g. `50GB` or `/home=20%`");
+        match input.rsplit_once('=') {
+            Some((path, amount)) => {
+                let free = match amount.trim().strip_suffix('%') {
+                    Some(percent) => percent
+                        .trim()
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|percent| (0.0..=100.0).contains(percent))
+                        .map(Amount::Percent),
+                    None => parse_size(amount).map(Amount::Bytes),
+                };
+                Ok(Goal::FreeOn {
+                    path: PathBuf::from(path.trim()),
+                    free: free.ok_or_else(invalid)?,
+                })
+            }
+            None => parse_size(input).map(Goal::Release).ok_or_else(invalid),
+        }
+    }
+}
+
+impl fmt::Display for Goal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Goal::Release(bytes) => write!(f, "release {}", format_size(*bytes)),
+            Goal::FreeOn { path, free } => match free {
+                Amount::Bytes(bytes) => {
+                    write!(f, "{} free on {}", format_size(*bytes), path.display())
+                }
+                Amount::Percent(percent) => write!(f, "{percent}% free on {}", path.display()),
+            },
+        }
+    }
+}
+
+/// The folders to delete to reach a [`Goal`], best candidates first.
+#[derive(Debug, Clone)]
+pub struct Plan {
+    pub goal: Goal,
+    pub folders: Vec<FolderInfo>,
+    /// Bytes freed by deleting all planned folders.
+    pub releases: u64,
+    /// Bytes that had to be freed when the plan was made.
+    pub needed: u64,
+}
+
+impl Plan {
+    /// Picks sized, not yet removed rows by the configured score rule until
+    /// they free enough space. Rows still being sized are not considered,
+    /// nor are protected ones when git protection blocks them.
+    pub fn new(goal: Goal, rows: &[FolderInfo], config: &Config) -> Result<Self> {
+        let (needed, mount) = match &goal {
+            Goal::Release(bytes) => (*bytes, None),
+            Goal::FreeOn { path, free } => {
+                // Rows carry absolute mount points, so `.` or `home` must be
+                // resolved before looking for theirs.
+                let mount = path
+                    .canonicalize()
+                    .ok()
+                    .and_then(|path| mounts::mount_point(&path))
+                    .ok_or_else(|| Error::Goal(format!("{} does not exist", path.display())))?;
+                let usage = mounts::usage(&mount).ok_or_else(|| {
+                    Error::Goal(format!("free space of {} is unknown", mount.display()))
+                })?;
+                let target = match free {
+                    Amount::Bytes(bytes) => *bytes,
+                    Amount::Percent(percent) => (usage.total as f64 * percent / 100.0) as u64,
+                };
+                (target.saturating_sub(usage.free), Some(mount))
+            }
+        };
+
+        let now = SystemTime::now();
+        let mut protected = HashMap::new();
+        let mut is_protected = |row: &FolderInfo| {
+            *protected
+                .entry(row.project.clone())
+                .or_insert_with(|| row.is_protected())
+        };
+        let mut candidates = rows
+            .iter()
+            .filter(|row| row.removal_status == ProcessStatus::Pending)
+            .filter(|row| row.dir_size().is_some())
+            .filter(|row| mount.is_none() || row.mount == mount)
+            .filter(|row| config.protect.git != GitProtection::Block || !is_protected(row))
+            .map(|row| (score(config.goal.score, &config.staleness, row, now), row))
+            .collect::<Vec<_>>();
+        candidates.sort_by(|(a, _), (b, _)| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+
+        let mut folders = Vec::new();
+        let mut freed = 0;
+        for (_, row) in candidates {
+            if freed >= needed {
+                break;
+            }
+            freed += row.exclusive_size().unwrap_or(0);
+            folders.push(row.clone());
+        }
+        let releases = releasable_size(folders.iter().filter_map(FolderInfo::dir_size));
+
+        Ok(Self {
+            goal,
+            folders,
+            releases,
+            needed,
+        })
+    }
+
+    pub fn reaches_goal(&self) -> bool {
+        self.releases >= self.needed
+    }
+
+    /// Planned folders of repositories with local work, read now. Each
+    /// repository is only asked once.
+    pub fn protected(&self) -> Vec<&FolderInfo> {
+        let mut projects = HashMap::new();
+        self.folders
+            .iter()
+            .filter(|folder| {
+                *projects
+                    .entry(&folder.project)
+                    .or_insert_with(|| folder.is_protected())
+            })
+            .collect()
+    }
+}
+
+fn score(
+    rule: ScoreRule,
+    staleness: &StalenessConfig,
+    folder: &FolderInfo,
+    now: SystemTime,
+) -> f64 {
+    let size = folder.exclusive_size().unwrap_or(0) as f64;
+    let age = folder
+        .last_modified()
+        .and_then(|modified| now.duration_since(modified).ok())
+        .unwrap_or(Duration::ZERO);
+    match rule {
+        ScoreRule::Size => size,
+        ScoreRule::Age => age.as_secs_f64(),
+        ScoreRule::AgeSize => size * (age.as_secs_f64() / 86400.0).max(1.0),
+        ScoreRule::Staleness => staleness::score(folder, staleness, now).unwrap_or(0) as f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::fs::calculate_dir_size;
+
+    #[test]
+    fn parses_goals() {
+        assert_eq!("50GB".parse(), Ok(Goal::Release(50 << 30)));
+        assert_eq!(
+            "/home=20%".parse(),
+            Ok(Goal::FreeOn {
+                path: PathBuf::from("/home"),
+                free: Amount::Percent(20.0),
+            })
+        );
+        assert_eq!(
+            ".=100GB".parse(),
+            Ok(Goal::FreeOn {
+                path: PathBuf::from("."),
+                free: Amount::Bytes(100 << 30),
+            })
+        );
+        assert_eq!(
+            "home = 12.5 %".parse(),
+            Ok(Goal::FreeOn {
+                path: PathBuf::from("home"),
+                free: Amount::Percent(12.5),
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_goals() {
+        for input in [
+            "",
+            "lots",
+            "50XB",
+            "/home=",
+            "/home=150%",
+            "/home=-5%",
+            "/home=big",
+        ] {
+            assert!(input.parse::<Goal>().is_err(), "{input} should be invalid");
+        }
+    }
+
+    #[test]
+    fn resolves_relative_goal_paths() {
+        let cwd = std::env::current_dir().unwrap();
+        let row = FolderInfo::new(cwd.join("src")).mount(mounts::mount_point(&cwd));
+        row.set_size(calculate_dir_size(&row.path, false));
+        let mut config = Config::default();
+        config.protect.git = GitProtection::Off;
+
+        let goal = Goal::FreeOn {
+            path: PathBuf::from("."),
+            free: Amount::Percent(100.0),
+        };
+        let plan = Plan::new(goal, std::slice::from_ref(&row), &config).unwrap();
+        assert_eq!(plan.folders.len(), 1);
+
+        let goal = Goal::FreeOn {
+            path: PathBuf::from("does-not-exist"),
+            free: Amount::Percent(100.0),
+        };
+        assert!(Plan::new(goal, &[row], &config).is_err());
+    }
+}
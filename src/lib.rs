@@ -1,9 +1,16 @@
 pub mod actions;
+pub mod cli;
+pub mod commands;
 pub mod config;
+pub mod detector;
+pub mod enricher;
 pub mod errors;
 pub mod events;
+pub mod goal;
 pub mod logging;
 pub mod models;
+pub mod removal;
+pub mod staleness;
 pub mod tui;
 pub mod ui;
 pub mod utils;
@@ -1,16 +1,294 @@
+use crate::utils::fs::state_dir;
+use chrono::Local;
 use color_eyre::Result;
-use log::LevelFilter;
-use simplelog::{CombinedLogger, Config, WriteLogger};
-use std::fs::File;
+use log::{
+    Level, LevelFilter, Log, Metadata, Record,
+    kv::{self, Key, Value, VisitSource},
+};
+use std::{
+    fmt::Write as _,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
 
-pub fn init() -> Result<()> {
-    let log_file = File::create("app.log")?;
+/// The log file is rotated once it grows past this size.
+const MAX_SIZE: u64 = 1024 * 1024;
 
-    CombinedLogger::init(vec![WriteLogger::new(
-        LevelFilter::Trace,
-        Config::default(),
-        log_file,
-    )])?;
+/// Rotated log files kept next to the current one.
+const ROTATED: usize = 3;
 
+/// Where logs go and how much of them.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options {
+    /// Number of `-v` minus number of `-q` on the command line.
+    pub verbosity: i16,
+    /// Also print records to stderr, for headless commands.
+    pub stderr: bool,
+}
+
+/// Logs to `vaporz.log` in the user state directory, at info level or as
+/// set by `RUST_LOG`-style directives in `VAPORZ_LOG` or `RUST_LOG`, moved up
+/// or down by `-v`/`-q`. With `stderr`, warnings and errors are printed too,
+/// or everything logged when `-v` is given.
+pub fn init(options: Options) -> Result<()> {
+    let mut filter = std::env::var("VAPORZ_LOG")
+        .or_else(|_| std::env::var("RUST_LOG"))
+        .map(|spec| Filter::parse(&spec))
+        .unwrap_or_else(|_| Filter::new(LevelFilter::Info));
+    filter.default = shift(filter.default, options.verbosity);
+    let stderr = match options.stderr {
+        true => shift(LevelFilter::Warn, options.verbosity),
+        false => LevelFilter::Off,
+    };
+
+    let file = log_path().and_then(|path| match LogFile::open(path.clone()) {
+        Ok(file) => Some(file),
+        Err(err) => {
+            eprintln!("vaporz: cannot write {}: {err}", path.display());
+            None
+        }
+    });
+
+    log::set_max_level(filter.max());
+    log::set_boxed_logger(Box::new(Logger {
+        filter,
+        stderr,
+        file: file.map(Mutex::new),
+    }))?;
     Ok(())
 }
+
+pub fn log_path() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("vaporz.log"))
+}
+
+/// Moves `level` up by `by` steps towards trace, or down towards off.
+fn shift(level: LevelFilter, by: i16) -> LevelFilter {
+    let index = (level as i16)
+        .saturating_add(by)
+        .clamp(0, LevelFilter::Trace as i16);
+    LevelFilter::iter()
+        .nth(index as usize)
+        .unwrap_or(LevelFilter::Trace)
+}
+
+/// `RUST_LOG`-style directives: `warn,vaporz::utils::scanner=debug`. A bare
+/// level applies to every module, a bare module enables all of its records.
+#[derive(Debug, Clone)]
+struct Filter {
+    default: LevelFilter,
+    /// Most specific module first.
+    modules: Vec<(String, LevelFilter)>,
+}
+
+impl Filter {
+    fn new(default: LevelFilter) -> Self {
+        Self {
+            default,
+            modules: Vec::new(),
+        }
+    }
+
+    fn parse(spec: &str) -> Self {
+        let mut filter = Filter::new(LevelFilter::Off);
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((module, level)) => {
+                    if let Ok(level) = level.trim().parse() {
+                        filter.modules.push((module.trim().to_string(), level));
+                    }
+                }
+                None => match directive.parse() {
+                    Ok(level) => filter.default = level,
+                    Err(_) => filter
+                        .modules
+                        .push((directive.to_string(), LevelFilter::Trace)),
+                },
+            }
+        }
+        filter
+            .modules
+            .sort_by_key(|(module, _)| std::cmp::Reverse(module.len()));
+        filter
+    }
+
+    fn level(&self, target: &str) -> LevelFilter {
+        self.modules
+            .iter()
+            .find(|(module, _)| {
+                target
+                    .strip_prefix(module.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+            })
+            .map_or(self.default, |(_, level)| *level)
+    }
+
+    fn max(&self) -> LevelFilter {
+        self.modules
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.default, std::cmp::max)
+    }
+}
+
+/// Writes to a rotated file and, for headless commands, to stderr at its own
+/// level. `simplelog` and `env_logger` cover either but not both, nor
+/// rotation, and the crates that do pull in far more than this needs.
+struct Logger {
+    filter: Filter,
+    /// Most verbose level printed to stderr.
+    stderr: LevelFilter,
+    file: Option<Mutex<LogFile>>,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.filter.level(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let mut fields = String::new();
+        let _ = record.key_values().visit(&mut Fields(&mut fields));
+
+        if let Some(file) = &self.file
+            && let Ok(mut file) = file.lock()
+        {
+            file.write_line(&format!(
+                "{} {:<5} {}: {}{fields}",
+                Local::now().format("%Y-%m-%dT%H:%M:%S%.3f%:z"),
+                record.level(),
+                record.target(),
+                record.args()
+            ));
+        }
+        if record.level() <= self.stderr {
+            let level = record.level().as_str().to_lowercase();
+            match record.level() {
+                Level::Error | Level::Warn => eprintln!("{level}: {}{fields}", record.args()),
+                _ => eprintln!("{}{fields}", record.args()),
+            }
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(file) = &self.file
+            && let Ok(mut file) = file.lock()
+        {
+            let _ = file.file.flush();
+        }
+    }
+}
+
+/// Appends the key-values of a record as ` key=value`.
+struct Fields<'a>(&'a mut String);
+
+impl<'kvs> VisitSource<'kvs> for Fields<'_> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
+        let value = value.to_string();
+        if value.is_empty() || value.contains(char::is_whitespace) {
+            let _ = write!(self.0, " {key}={value:?}");
+        } else {
+            let _ = write!(self.0, " {key}={value}");
+        }
+        Ok(())
+    }
+}
+
+/// The log file, rotated to `vaporz.1.log`, `vaporz.2.log`, ... when it
+/// grows past [`MAX_SIZE`].
+struct LogFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+}
+
+impl LogFile {
+    fn open(path: PathBuf) -> io::Result<Self> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self { path, file, size })
+    }
+
+    fn write_line(&mut self, line: &str) {
+        if self.size >= MAX_SIZE
+            && let Ok(rotated) = self.rotate()
+        {
+            *self = rotated;
+        }
+        if writeln!(self.file, "{line}").is_ok() {
+            self.size += line.len() as u64 + 1;
+        }
+    }
+
+    fn rotate(&self) -> io::Result<Self> {
+        let _ = fs::remove_file(rotated_path(&self.path, ROTATED));
+        for index in (1..ROTATED).rev() {
+            let from = rotated_path(&self.path, index);
+            if from.exists() {
+                fs::rename(from, rotated_path(&self.path, index + 1))?;
+            }
+        }
+        fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        Self::open(self.path.clone())
+    }
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    path.with_extension(format!("{index}.log"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shifts_levels() {
+        assert_eq!(shift(LevelFilter::Info, 1), LevelFilter::Debug);
+        assert_eq!(shift(LevelFilter::Info, -2), LevelFilter::Error);
+        assert_eq!(shift(LevelFilter::Info, i16::MAX), LevelFilter::Trace);
+        assert_eq!(shift(LevelFilter::Warn, i16::MIN), LevelFilter::Off);
+    }
+
+    #[test]
+    fn parses_a_bare_level() {
+        let filter = Filter::parse("debug");
+        assert_eq!(filter.level("vaporz::removal"), LevelFilter::Debug);
+        assert_eq!(filter.max(), LevelFilter::Debug);
+    }
+
+    #[test]
+    fn prefers_the_most_specific_module() {
+        let filter = Filter::parse("warn, vaporz=info ,vaporz::utils::scanner=trace");
+        assert_eq!(filter.level("vaporz::removal"), LevelFilter::Info);
+        assert_eq!(filter.level("vaporz::utils::scanner"), LevelFilter::Trace);
+        assert_eq!(
+            filter.level("vaporz::utils::scanner::x"),
+            LevelFilter::Trace
+        );
+        assert_eq!(filter.level("tokio"), LevelFilter::Warn);
+        assert_eq!(filter.max(), LevelFilter::Trace);
+    }
+
+    #[test]
+    fn matches_whole_module_names() {
+        let filter = Filter::parse("vaporz::ui");
+        assert_eq!(filter.level("vaporz::ui::app"), LevelFilter::Trace);
+        assert_eq!(filter.level("vaporz::uix"), LevelFilter::Off);
+        assert_eq!(filter.level("vaporz"), LevelFilter::Off);
+    }
+
+    #[test]
+    fn skips_invalid_levels() {
+        let filter = Filter::parse("error,vaporz=loud,,");
+        assert!(filter.modules.is_empty());
+        assert_eq!(filter.level("vaporz"), LevelFilter::Error);
+    }
+}
@@ -1,14 +1,33 @@
+use clap::Parser;
 use color_eyre::eyre::Result;
-use vaporz::{logging, tui::Tui, ui::app::App};
+use vaporz::{
+    cli::{Cli, Command},
+    commands, config, logging,
+    tui::Tui,
+    ui::app::App,
+};
 
 #[tokio::main]
 async fn main() -> Result<()> {
     color_eyre::install()?;
-    logging::init()?;
+    let cli = Cli::parse();
+    logging::init(cli.logging())?;
+    let mut config = config::load_config()?;
+    cli.apply(&mut config);
+
+    if let Some(command) = cli.command {
+        match command {
+            Command::Free { goal, yes } => commands::free::run(config, goal, yes)?,
+            Command::Restore { path } => commands::restore::run(config, path)?,
+            Command::History { limit } => commands::history::run(limit)?,
+        }
+        return Ok(());
+    }
+
     let tui = Tui::new()?
         .tick_rate(1.0) // 4 ticks per second
         .frame_rate(2.0); // 30 frames per second
-    let mut app = App::default();
+    let mut app = App::new(config);
     let result = app.run(tui).await;
     result?;
     Ok(())
@@ -1,12 +1,16 @@
+use crate::config::Strategy;
+use crate::detector::Directory;
 use crate::utils::{
-    fs::{calculate_dir_size, last_modified},
-    humanize,
+    fs::DirSize,
+    git,
+    humanize::{self, DateFormat},
+    scan_cache::Fingerprint,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::{
-    fs,
     path::{Path, PathBuf},
     sync::{Arc, OnceLock},
+    time::SystemTime,
 };
 use uuid::Uuid;
 
@@ -19,51 +23,202 @@ pub enum ProcessStatus {
     Failed,
 }
 
+/// Git activity of the project a folder belongs to. All fields are empty
+/// outside of git repositories.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Activity {
+    pub last_commit: Option<SystemTime>,
+    pub dirty: bool,
+    #[serde(default)]
+    pub stashes: usize,
+    #[serde(default)]
+    pub ahead: usize,
+}
+
+impl Activity {
+    /// Reads the last commit and the local work of the repository `project`
+    /// belongs to.
+    pub fn read(project: &Path) -> Self {
+        let status = git::status(project).unwrap_or_default();
+        Self {
+            last_commit: git::last_commit_time(project),
+            dirty: status.dirty,
+            stashes: status.stashes,
+            ahead: status.ahead,
+        }
+    }
+
+    /// Whether the repository has uncommitted changes, stashes or unpushed
+    /// commits.
+    pub fn has_local_work(&self) -> bool {
+        self.dirty || self.stashes > 0 || self.ahead > 0
+    }
+
+    /// A short description of the local work, like `dirty, 1 stash, 2 ahead`.
+    pub fn local_work(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if self.dirty {
+            parts.push(String::from("dirty"));
+        }
+        match self.stashes {
+            0 => {}
+            1 => parts.push(String::from("1 stash")),
+            stashes => parts.push(format!("{stashes} stashes")),
+        }
+        if self.ahead > 0 {
+            parts.push(format!("{} ahead", self.ahead));
+        }
+        (!parts.is_empty()).then(|| parts.join(", "))
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct FolderInfo {
     pub id: Uuid,
     pub path: PathBuf,
     pub removal_status: ProcessStatus,
-    size: Arc<OnceLock<u64>>,
-    last_modified: Arc<OnceLock<u64>>,
+    pub target: String,
+    pub details: Option<String>,
+    pub project: PathBuf,
+    pub workspace: Option<PathBuf>,
+    pub fingerprint: Fingerprint,
+    pub mount: Option<PathBuf>,
+    /// Why the last removal attempt failed.
+    pub removal_error: Option<String>,
+    size: Arc<OnceLock<DirSize>>,
+    last_modified: Arc<OnceLock<SystemTime>>,
+    activity: Arc<OnceLock<Activity>>,
 }
 
 impl FolderInfo {
     pub fn new(path: PathBuf) -> Self {
-        let info = Self {
+        Self {
             id: Uuid::new_v4(),
+            project: path.parent().unwrap_or(&path).to_path_buf(),
             path,
             size: Arc::new(OnceLock::new()),
             last_modified: Arc::new(OnceLock::new()),
+            activity: Arc::new(OnceLock::new()),
             removal_status: ProcessStatus::default(),
-        };
-        info.bg();
-        info
+            target: String::new(),
+            details: None,
+            workspace: None,
+            fingerprint: Fingerprint::default(),
+            mount: None,
+            removal_error: None,
+        }
+    }
+
+    pub fn target(mut self, target: &str) -> Self {
+        self.target = target.to_string();
+        self
+    }
+
+    pub fn details(mut self, details: Option<String>) -> Self {
+        self.details = details;
+        self
+    }
+
+    pub fn project(mut self, project: PathBuf) -> Self {
+        self.project = project;
+        self
+    }
+
+    pub fn workspace(mut self, workspace: Option<PathBuf>) -> Self {
+        self.workspace = workspace;
+        self
+    }
+
+    pub fn fingerprint(mut self, fingerprint: Fingerprint) -> Self {
+        self.fingerprint = fingerprint;
+        self
+    }
+
+    pub fn mount(mut self, mount: Option<PathBuf>) -> Self {
+        self.mount = mount;
+        self
+    }
+
+    /// A copy of this folder with its size and modification time cleared,
+    /// so they are calculated again.
+    pub fn refresh(&self) -> Self {
+        FolderInfo::new(self.path.clone())
+            .target(&self.target)
+            .details(self.details.clone())
+            .project(self.project.clone())
+            .workspace(self.workspace.clone())
+            .fingerprint(Fingerprint::read(&self.path, &self.project))
+            .mount(self.mount.clone())
     }
 
     pub fn path_string(&self) -> String {
         self.path.display().to_string()
     }
 
+    /// A name to keep the folder under outside its project, like
+    /// `app-target-1a2b3c4d`. The random suffix keeps folders of the same
+    /// name apart.
+    pub fn unique_name(&self) -> String {
+        let name = |path: &Path| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        };
+        let id = Uuid::new_v4().simple().to_string();
+        format!("{}-{}-{}", name(&self.project), name(&self.path), &id[..8])
+    }
+
     pub fn size(&self) -> Option<u64> {
-        self.size.get().copied()
+        self.size.get().map(|size| size.allocated)
+    }
+
+    pub fn exclusive_size(&self) -> Option<u64> {
+        self.size.get().map(|size| size.exclusive)
     }
 
-    pub fn last_modified(&self) -> Option<u64> {
+    pub fn dir_size(&self) -> Option<&DirSize> {
+        self.size.get()
+    }
+
+    pub fn apparent_size(&self) -> Option<u64> {
+        self.size.get().map(|size| size.apparent)
+    }
+
+    pub fn size_errors(&self) -> u64 {
+        self.size.get().map(|size| size.errors).unwrap_or_default()
+    }
+
+    pub fn last_modified(&self) -> Option<SystemTime> {
         self.last_modified.get().copied()
     }
 
-    fn bg(&self) {
-        let (path, cell) = (self.path.clone(), self.size.clone());
-        tokio::task::spawn_blocking(move || {
-            let size = calculate_dir_size(path);
-            let _ = cell.set(size);
-        });
-        let (path, cell) = (self.path.clone(), self.last_modified.clone());
-        tokio::task::spawn_blocking(move || {
-            let elapsed = last_modified(path).unwrap_or_default();
-            let _ = cell.set(elapsed);
-        });
+    pub fn activity(&self) -> Option<Activity> {
+        self.activity.get().copied()
+    }
+
+    /// Whether the folder's repository has local work right now. The
+    /// repository is read again on every call rather than trusting
+    /// [`FolderInfo::activity`], which may predate the work to protect.
+    pub fn is_protected(&self) -> bool {
+        Activity::read(&self.project).has_local_work()
+    }
+
+    pub fn is_enriched(&self) -> bool {
+        self.size.get().is_some()
+            && self.last_modified.get().is_some()
+            && self.activity.get().is_some()
+    }
+
+    pub fn set_size(&self, size: DirSize) {
+        let _ = self.size.set(size);
+    }
+
+    pub fn set_last_modified(&self, modified: SystemTime) {
+        let _ = self.last_modified.set(modified);
+    }
+
+    pub fn set_activity(&self, activity: Activity) {
+        let _ = self.activity.set(activity);
     }
 }
 
@@ -72,8 +227,12 @@ impl FolderInfo {
         Some(humanize::format_size(self.size()?))
     }
 
-    pub fn human_last_modified(&self) -> Option<String> {
-        Some(humanize::format_last_modified(self.last_modified()?))
+    pub fn human_exclusive_size(&self) -> Option<String> {
+        Some(humanize::format_size(self.exclusive_size()?))
+    }
+
+    pub fn human_last_modified(&self, format: DateFormat) -> Option<String> {
+        Some(humanize::format_date(self.last_modified()?, format))
     }
 }
 
@@ -82,20 +241,52 @@ pub struct TargetInfo {
     pub name: String,
     pub markers: Vec<String>,
     pub artifacts: Vec<String>,
+    /// Command run in the project root to remove the artifacts, e.g.
+    /// `cargo clean`. They are deleted directly when it is not set.
+    #[serde(default)]
+    pub clean_command: Option<String>,
+    /// How the artifacts are removed: cleaned when a `clean_command` is set,
+    /// deleted otherwise.
+    #[serde(default)]
+    pub strategy: Option<Strategy>,
 }
 
 impl TargetInfo {
-    pub fn is_project_root(&self, dir: &Path) -> bool {
+    pub fn is_project_root(&self, dir: &Directory) -> bool {
         self.markers.iter().any(|marker| {
             if let Some(suffix) = marker.strip_prefix("ext:") {
-                fs::read_dir(dir)
-                    .ok()
-                    .into_iter()
-                    .flat_map(|it| it.filter_map(Result::ok))
-                    .any(|entry| entry.path().extension().is_some_and(|ext| ext == suffix))
+                dir.has_extension(suffix)
             } else {
-                dir.join(marker).exists()
+                dir.contains(marker)
             }
         })
     }
 }
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct CacheInfo {
+    pub name: String,
+    pub paths: Vec<String>,
+}
+
+impl CacheInfo {
+    pub fn locate(&self) -> Option<PathBuf> {
+        self.paths
+            .iter()
+            .filter_map(|path| expand_path(path))
+            .find(|path| path.is_dir())
+    }
+}
+
+pub(crate) fn expand_path(path: &str) -> Option<PathBuf> {
+    let mut expanded = PathBuf::new();
+    for (index, component) in path.split('/').enumerate() {
+        match component {
+            "" if index == 0 => expanded.push("/"),
+            "~" if index == 0 => expanded.push(dirs::home_dir()?),
+            var if var.starts_with('$') => expanded.push(std::env::var_os(&var[1..])?),
+            component => expanded.push(component),
+        }
+    }
+    Some(expanded)
+}
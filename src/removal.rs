@@ -0,0 +1,151 @@
+use crate::{
+    config::{ArchiveConfig, Config, RelocateConfig, Strategy},
+    errors::{Error, Result},
+    models::FolderInfo,
+    utils::{
+        archive,
+        fs::remove_path,
+        history::{self, Entry},
+        relocate,
+    },
+};
+use std::{io, path::Path, process::Command};
+
+/// How a folder is got rid of.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Removal {
+    /// Deletes the folder directly.
+    Delete,
+    /// Runs the ecosystem's own clean tool, e.g. `cargo clean`, in the
+    /// project root, then deletes whatever the tool left of the folder.
+    Clean(String),
+    /// Moves the folder into a tarball in the archive directory, to be
+    /// restored with `vaporz restore`.
+    Archive(ArchiveConfig),
+    /// Moves the folder to the relocation directory, usually on another
+    /// disk, to be moved back with `vaporz restore`.
+    Relocate(RelocateConfig),
+}
+
+impl Removal {
+    /// Follows the `strategy` of the folder's target. Without one, targets
+    /// with a `clean_command` are cleaned and everything else is deleted.
+    pub fn for_folder(folder: &FolderInfo, config: &Config) -> Self {
+        let Some(target) = config
+            .targets
+            .iter()
+            .find(|target| target.name == folder.target)
+        else {
+            return Removal::Delete;
+        };
+        match (target.strategy, &target.clean_command) {
+            (Some(Strategy::Archive), _) => Removal::Archive(config.archive.clone()),
+            (Some(Strategy::Relocate), _) => Removal::Relocate(config.relocate.clone()),
+            (Some(Strategy::Delete), _) | (_, None) => Removal::Delete,
+            (Some(Strategy::Clean) | None, Some(command)) => Removal::Clean(command.clone()),
+        }
+    }
+
+    pub fn strategy(&self) -> Strategy {
+        match self {
+            Removal::Delete => Strategy::Delete,
+            Removal::Clean(_) => Strategy::Clean,
+            Removal::Archive(_) => Strategy::Archive,
+            Removal::Relocate(_) => Strategy::Relocate,
+        }
+    }
+
+    /// Removes the folder and records the outcome in the history.
+    pub fn run(&self, folder: &FolderInfo) -> Result<()> {
+        let result = self.apply(folder);
+        match &result {
+            Ok(()) => log::info!(
+                path:% = folder.path.display(),
+                ecosystem = folder.target.as_str(),
+                strategy:% = self.strategy(),
+                freed = folder.exclusive_size();
+                "removed"
+            ),
+            Err(err) => log::error!(
+                path:% = folder.path.display(),
+                ecosystem = folder.target.as_str(),
+                strategy:% = self.strategy(),
+                error:% = err;
+                "removal failed"
+            ),
+        }
+        history::record(&Entry::new(folder, self.strategy(), &result));
+        result
+    }
+
+    fn apply(&self, folder: &FolderInfo) -> Result<()> {
+        match self {
+            Removal::Delete => remove_path(&folder.path),
+            Removal::Clean(command) => {
+                clean(command, &folder.project)?;
+                // Tools only clean what they know about, e.g. `cargo clean`
+                // leaves `.xwin-cache` alone.
+                remove_path(&folder.path)
+            }
+            Removal::Archive(config) => {
+                let file = archive::archive(folder, config)?;
+                log::info!("archived {} to {}", folder.path.display(), file.display());
+                Ok(())
+            }
+            Removal::Relocate(config) => {
+                let to = relocate::relocate(folder, config)?;
+                log::info!("relocated {} to {}", folder.path.display(), to.display());
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Runs `command` in `project`. A missing tool is not an error: the caller
+/// falls back to deleting the folder itself.
+fn clean(command: &str, project: &Path) -> Result<()> {
+    let mut args = command.split_whitespace();
+    let Some(program) = args.next() else {
+        return Ok(());
+    };
+    let output = match Command::new(program)
+        .args(args)
+        .current_dir(project)
+        .output()
+    {
+        Ok(output) => output,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            log::warn!("{program} not found, deleting without `{command}`");
+            return Ok(());
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if output.status.success() {
+        log::info!(
+            "`{command}` in {}: {}\n{stdout}{stderr}",
+            project.display(),
+            output.status
+        );
+        return Ok(());
+    }
+    log::error!(
+        "`{command}` in {}: {}\n{stdout}{stderr}",
+        project.display(),
+        output.status
+    );
+    let reason = stderr
+        .lines()
+        .rev()
+        .chain(stdout.lines().rev())
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(|line| format!(": {line}"))
+        .unwrap_or_default();
+    Err(Error::Clean(format!(
+        "`{command}` {}{reason}",
+        output.status
+    )))
+}
@@ -0,0 +1,48 @@
+use crate::{config::StalenessConfig, models::FolderInfo};
+use std::time::{Duration, SystemTime};
+
+const DAY: f64 = 86400.0;
+
+/// How likely a folder is to be safe to delete, from 0 (in active use) to
+/// 100 (large and untouched for long). `None` until the folder is enriched.
+///
+/// Age since the last change or commit counts for most of the score, size
+/// for the rest. Uncommitted work cuts the score to a quarter.
+pub fn score(folder: &FolderInfo, config: &StalenessConfig, now: SystemTime) -> Option<u8> {
+    let activity = folder.activity()?;
+    let idle = idle_days(folder, now)?;
+    let size = folder.exclusive_size()? as f64;
+
+    let age = (idle / config.stale_after_days.max(1) as f64).min(1.0);
+    let size = ((size / (1024.0 * 1024.0)).max(1.0).log10() / 4.0).min(1.0);
+    let mut score = 100.0 * (0.7 * age + 0.3 * size);
+    if activity.dirty {
+        score /= 4.0;
+    }
+    Some(score.round() as u8)
+}
+
+/// Whether a folder belongs in the "recommended for deletion" view: stale
+/// enough, in a project without uncommitted work that has been idle for
+/// at least `fresh_days`.
+pub fn is_recommended(folder: &FolderInfo, config: &StalenessConfig, now: SystemTime) -> bool {
+    let (Some(score), Some(activity), Some(idle)) = (
+        score(folder, config, now),
+        folder.activity(),
+        idle_days(folder, now),
+    ) else {
+        return false;
+    };
+    score >= config.recommend_above && !activity.dirty && idle >= config.fresh_days as f64
+}
+
+/// Days since the folder or its project's last commit changed, whichever is
+/// more recent.
+fn idle_days(folder: &FolderInfo, now: SystemTime) -> Option<f64> {
+    let activity = folder.activity()?;
+    let last = folder
+        .last_modified()?
+        .max(activity.last_commit.unwrap_or(SystemTime::UNIX_EPOCH));
+    let idle = now.duration_since(last).unwrap_or(Duration::ZERO);
+    Some(idle.as_secs_f64() / DAY)
+}
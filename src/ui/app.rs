@@ -1,10 +1,22 @@
 use crate::errors::Result;
-use crate::ui::metrics::MetricsWidget;
+use crate::ui::metrics::{MetricsWidget, UsageCache};
 use crate::{
     actions::AppAction,
     events::AppEvent,
     tui::Tui,
     ui::artifacts::{ArtifacsWidget, Artifacts},
+    ui::caches::{Caches, CachesWidget},
+    ui::goal::{GoalState, GoalWidget},
+    ui::history::{History, HistoryWidget},
+    ui::protected::ProtectedWidget,
+};
+use crate::{
+    config::{self, Config, GitProtection},
+    enricher::FsEnricher,
+    errors::Error,
+    goal::{Goal, Plan},
+    models::FolderInfo,
+    utils::{humanize::DateFormat, scheduler::SizeScheduler},
 };
 use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{
@@ -13,27 +25,51 @@ use ratatui::{
     style::{Color, Style},
     widgets::{Block, StatefulWidget, Widget},
 };
+use std::{collections::HashSet, path::PathBuf};
 use tokio::sync::mpsc::{self, UnboundedReceiver};
 
 #[derive(Debug, Clone, PartialEq, Eq, Default, Copy)]
 pub enum AppMode {
     #[default]
     Artifacts,
+    Caches,
+    History,
 }
 
 pub struct App {
     pub mode: AppMode,
     pub artifacts: Artifacts,
+    pub caches: Caches,
+    pub history: History,
+    pub date_format: DateFormat,
+    pub goal: GoalState,
+    /// Protected folders waiting for an extra confirmation before removal.
+    pub protected: Vec<FolderInfo>,
+    usage: UsageCache,
+    config: Config,
     should_quit: bool,
     action_rx: UnboundedReceiver<AppAction>,
 }
 
 impl App {
-    fn new() -> Self {
+    pub fn new(config: Config) -> Self {
         let (action_tx, action_rx) = mpsc::unbounded_channel();
+        let scheduler = SizeScheduler::new(config.sizing, FsEnricher::new(&config));
         Self {
             mode: AppMode::default(),
-            artifacts: Artifacts::new(action_tx.clone()),
+            protected: Vec::new(),
+            usage: UsageCache::default(),
+            caches: Caches::new(
+                action_tx.clone(),
+                scheduler.clone(),
+                config.caches.clone(),
+                config.staleness,
+            ),
+            artifacts: Artifacts::new(action_tx.clone(), scheduler, config.clone()),
+            history: History::default(),
+            config,
+            date_format: DateFormat::default(),
+            goal: GoalState::default(),
             should_quit: false,
             action_rx,
         }
@@ -42,7 +78,7 @@ impl App {
 
 impl Default for App {
     fn default() -> Self {
-        Self::new()
+        Self::new(config::load_config().unwrap_or_default())
     }
 }
 
@@ -62,11 +98,27 @@ impl App {
     }
 
     fn handle_key_event(&mut self, kev: KeyEvent) -> Option<AppAction> {
+        if !self.protected.is_empty() {
+            return match kev.code {
+                KeyCode::Enter | KeyCode::Char('y') => Some(AppAction::ProtectedRemovalConfirm),
+                KeyCode::Esc | KeyCode::Char('n') => Some(AppAction::ProtectedRemovalCancel),
+                _ => None,
+            };
+        }
+        if self.goal.is_open() {
+            return self.goal.handle_key_event(kev);
+        }
         match kev.code {
             KeyCode::Esc | KeyCode::Char('q') => Some(AppAction::Quit),
             KeyCode::Char('1') => Some(AppAction::SwitchMode(AppMode::Artifacts)),
+            KeyCode::Char('2') => Some(AppAction::SwitchMode(AppMode::Caches)),
+            KeyCode::Char('3') => Some(AppAction::SwitchMode(AppMode::History)),
+            KeyCode::Char('d') => Some(AppAction::CycleDateFormat),
+            KeyCode::Char('g') if self.mode != AppMode::History => Some(AppAction::GoalOpen),
             _ => match self.mode {
                 AppMode::Artifacts => self.artifacts.handle_key_event(kev),
+                AppMode::Caches => self.caches.handle_key_event(kev),
+                AppMode::History => self.history.handle_key_event(kev),
             },
         }
     }
@@ -75,14 +127,127 @@ impl App {
         match action {
             AppAction::Quit => Ok(self.quit()),
             AppAction::SwitchMode(mode) => Ok(self.switch_mode(mode)),
-            AppAction::ArtifactsInsertRow(_) => self.artifacts.perform(action),
+            AppAction::CycleDateFormat => {
+                self.date_format = self.date_format.next();
+                Ok(Some(AppAction::Render))
+            }
+            AppAction::GoalOpen
+            | AppAction::GoalInput(_)
+            | AppAction::GoalBackspace
+            | AppAction::GoalSubmit
+            | AppAction::GoalConfirm
+            | AppAction::GoalCancel => self.perform_goal(action),
+            AppAction::ProtectedRemovalRequest(_)
+            | AppAction::ProtectedRemovalConfirm
+            | AppAction::ProtectedRemovalCancel => self.perform_protected(action),
+            AppAction::ArtifactsInsertRow(_) | AppAction::ArtifactsDropRow(_) => {
+                self.artifacts.perform(action)
+            }
+            AppAction::CachesInsertRow(_) => self.caches.perform(action),
             _ => match self.mode {
                 AppMode::Artifacts => self.artifacts.perform(action),
+                AppMode::Caches => self.caches.perform(action),
+                AppMode::History => self.history.perform(action),
             },
         }
     }
 
+    fn perform_goal(&mut self, action: AppAction) -> Result<Option<AppAction>> {
+        match action {
+            AppAction::GoalOpen => {
+                self.goal = GoalState::Editing {
+                    input: String::new(),
+                    error: None,
+                };
+            }
+            AppAction::GoalInput(c) => {
+                if let GoalState::Editing { input, .. } = &mut self.goal {
+                    input.push(c);
+                }
+            }
+            AppAction::GoalBackspace => {
+                if let GoalState::Editing { input, .. } = &mut self.goal {
+                    input.pop();
+                }
+            }
+            AppAction::GoalSubmit => {
+                if let GoalState::Editing { input, error } = &mut self.goal {
+                    let rows = match self.mode {
+                        AppMode::Artifacts => self.artifacts.rows.read()?.clone(),
+                        AppMode::Caches => self.caches.rows.read()?.clone(),
+                        AppMode::History => Vec::new(),
+                    };
+                    let plan = input
+                        .parse::<Goal>()
+                        .map_err(Error::Goal)
+                        .and_then(|goal| Plan::new(goal, &rows, &self.config));
+                    match plan {
+                        Ok(plan) => self.goal = GoalState::Confirm(plan),
+                        Err(err) => *error = Some(err.to_string()),
+                    }
+                }
+            }
+            AppAction::GoalConfirm => {
+                if let GoalState::Confirm(plan) = std::mem::take(&mut self.goal) {
+                    let protected: HashSet<PathBuf> =
+                        if self.config.protect.git == GitProtection::Confirm {
+                            plan.protected()
+                                .into_iter()
+                                .map(|folder| folder.path.clone())
+                                .collect()
+                        } else {
+                            HashSet::new()
+                        };
+                    let (protected, folders): (Vec<_>, Vec<_>) = plan
+                        .folders
+                        .into_iter()
+                        .partition(|folder| protected.contains(&folder.path));
+                    for folder in &folders {
+                        self.remove(folder)?;
+                    }
+                    if !protected.is_empty() {
+                        return Ok(Some(AppAction::ProtectedRemovalRequest(protected)));
+                    }
+                }
+            }
+            AppAction::GoalCancel => {
+                self.goal = GoalState::Closed;
+            }
+            _ => {}
+        }
+        Ok(Some(AppAction::Render))
+    }
+
+    fn perform_protected(&mut self, action: AppAction) -> Result<Option<AppAction>> {
+        match action {
+            AppAction::ProtectedRemovalRequest(folders) => {
+                self.protected = folders;
+            }
+            AppAction::ProtectedRemovalConfirm => {
+                for folder in std::mem::take(&mut self.protected) {
+                    self.remove(&folder)?;
+                }
+            }
+            AppAction::ProtectedRemovalCancel => {
+                self.protected.clear();
+            }
+            _ => {}
+        }
+        Ok(Some(AppAction::Render))
+    }
+
+    fn remove(&self, folder: &FolderInfo) -> Result<()> {
+        match self.mode {
+            AppMode::Artifacts => self.artifacts.remove(folder),
+            AppMode::Caches => self.caches.remove(folder),
+            AppMode::History => Ok(()),
+        }
+    }
+
     fn switch_mode(&mut self, mode: AppMode) -> Option<AppAction> {
+        if mode == AppMode::History {
+            self.history.load();
+        }
         self.mode = mode;
         Some(AppAction::Render)
     }
@@ -96,6 +261,7 @@ impl App {
         tui.enter()?;
 
         self.artifacts.load_data();
+        self.caches.load_data();
 
         loop {
             tokio::select! {
@@ -132,6 +298,7 @@ impl App {
         }
 
         tui.exit()?;
+        self.artifacts.save_cache();
         Ok(())
     }
 }
@@ -150,20 +317,69 @@ impl StatefulWidget for AppWidget {
         let background = Block::default().style(Style::default().bg(Color::Rgb(0, 0, 0)));
         background.render(area, buf);
 
+        let (mounts, saved_space) = match state.mode {
+            AppMode::Artifacts | AppMode::History => (
+                state.artifacts.releasable_by_mount(),
+                state.artifacts.saved_space(),
+            ),
+            AppMode::Caches => (
+                state.caches.releasable_by_mount(),
+                state.caches.saved_space(),
+            ),
+        };
+        let mounts = mounts.unwrap_or_default();
+        let saved_space = saved_space.unwrap_or(0);
+
+        let metrics = MetricsWidget {
+            releasable_space: mounts.iter().map(|(_, size)| size).sum(),
+            saved_space,
+            mounts: mounts
+                .into_iter()
+                .filter_map(|(mount, size)| {
+                    let mount = mount?;
+                    let usage = state.usage.get(&mount, saved_space);
+                    Some((mount, size, usage))
+                })
+                .collect(),
+        };
+
         let [metrics_area, artifacs_area] = Layout::default()
             .direction(Direction::Vertical)
-            .constraints(vec![Constraint::Min(4), Constraint::Percentage(100)])
+            .constraints(vec![
+                Constraint::Length(metrics.height()),
+                Constraint::Percentage(100),
+            ])
             .areas(area);
 
-        MetricsWidget {
-            releasable_space: state.artifacts.releasable_space().unwrap_or(0),
-            saved_space: state.artifacts.saved_space().unwrap_or(0),
+        metrics.render(metrics_area, buf);
+
+        match state.mode {
+            AppMode::Artifacts => ArtifacsWidget {
+                has_focus: true,
+                date_format: state.date_format,
+            }
+            .render(artifacs_area, buf, &mut state.artifacts),
+            AppMode::Caches => CachesWidget {
+                has_focus: true,
+                date_format: state.date_format,
+            }
+            .render(artifacs_area, buf, &mut state.caches),
+            AppMode::History => HistoryWidget {
+                has_focus: true,
+                date_format: state.date_format,
+            }
+            .render(artifacs_area, buf, &mut state.history),
+        }
+
+        GoalWidget {
+            state: &state.goal,
+            date_format: state.date_format,
         }
-        .render(metrics_area, buf);
+        .render(area, buf);
 
-        ArtifacsWidget {
-            has_focus: state.mode == AppMode::Artifacts,
+        ProtectedWidget {
+            folders: &state.protected,
         }
-        .render(artifacs_area, buf, &mut state.artifacts);
+        .render(area, buf);
     }
 }
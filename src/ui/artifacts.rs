@@ -1,10 +1,21 @@
 use crate::errors::Result;
-use crate::ui::loading::LoadingLine;
-use crate::utils;
+use crate::ui::{
+    live::LiveScan,
+    loading::LoadingLine,
+    metrics::{MountSizes, releasable_by_mount},
+};
+use crate::utils::{
+    fs::releasable_size,
+    humanize::{self, DateFormat},
+    scheduler::SizeScheduler,
+};
 use crate::{
     actions::AppAction,
+    config::{Config, GitProtection, StalenessConfig},
     models::{FolderInfo, ProcessStatus},
-    utils::scanner::scan_current_dir,
+    removal::Removal,
+    staleness,
+    utils::{scan_cache::ScanCache, scanner::find_target_dirs},
 };
 use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{
@@ -14,28 +25,72 @@ use ratatui::{
     text::{Line, Span},
     widgets::{Block, BorderType, Cell, Row, StatefulWidget, Table, TableState},
 };
-use std::sync::{Arc, RwLock};
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+    time::SystemTime,
+};
 use tokio::sync::mpsc::{self, UnboundedSender};
 use uuid::Uuid;
 
+pub enum TableEntry {
+    Folder(usize),
+    Workspace {
+        root: PathBuf,
+        members: Vec<usize>,
+        expanded: bool,
+    },
+    Member(usize),
+}
+
 pub struct Artifacts {
     pub rows: Arc<RwLock<Vec<FolderInfo>>>,
     pub table_state: TableState,
     pub action_tx: UnboundedSender<AppAction>,
+    scheduler: SizeScheduler,
+    config: Config,
+    root: Option<PathBuf>,
+    live: Option<LiveScan>,
+    expanded_workspaces: HashSet<PathBuf>,
+    /// Bumped whenever rows are added, replaced or dropped.
+    generation: u64,
+    /// Releasable sizes by generation and number of sized rows.
+    releasable_cache: std::cell::RefCell<Option<((u64, usize), MountSizes)>>,
     path_order_descending: bool,
     last_modified_order_descending: bool,
     size_order_descending: bool,
+    staleness_order_descending: bool,
+    /// Only show folders recommended for deletion.
+    recommended: bool,
+    /// Why the last removal left some rows alone, shown until the next one.
+    skipped: Option<String>,
 }
 
 impl Artifacts {
-    pub fn new(action_tx: UnboundedSender<AppAction>) -> Self {
+    pub fn new(
+        action_tx: UnboundedSender<AppAction>,
+        scheduler: SizeScheduler,
+        config: Config,
+    ) -> Self {
         Self {
             rows: Arc::new(RwLock::new(Vec::new())),
             table_state: TableState::new(),
             action_tx,
+            scheduler,
+            config,
+            root: None,
+            live: None,
+            expanded_workspaces: HashSet::new(),
+            generation: 0,
+            releasable_cache: std::cell::RefCell::new(None),
             path_order_descending: Default::default(),
             last_modified_order_descending: Default::default(),
             size_order_descending: Default::default(),
+            staleness_order_descending: Default::default(),
+            recommended: false,
+            skipped: None,
         }
     }
 }
@@ -45,10 +100,14 @@ impl Artifacts {
         match kev.code {
             KeyCode::Up => Some(AppAction::ArtifactsSelectPreviousRow),
             KeyCode::Down => Some(AppAction::ArtifactsSelectNextRow),
+            KeyCode::Right => Some(AppAction::ArtifactsExpandRow),
+            KeyCode::Left => Some(AppAction::ArtifactsCollapseRow),
             KeyCode::Enter => Some(AppAction::ArtifactsRemoveRow),
             KeyCode::Char('m') => Some(AppAction::ArtifactsSortByLastMod),
             KeyCode::Char('p') => Some(AppAction::ArtifactsSortByPath),
             KeyCode::Char('s') => Some(AppAction::ArtifactsSortBySize),
+            KeyCode::Char('t') => Some(AppAction::ArtifactsSortByStaleness),
+            KeyCode::Char('r') => Some(AppAction::ArtifactsToggleRecommended),
             _ => None,
         }
     }
@@ -64,8 +123,19 @@ impl Artifacts {
             AppAction::ArtifactsInsertRow(row) => {
                 self.insert_row(row);
             }
+            AppAction::ArtifactsDropRow(path) => {
+                self.drop_row(&path);
+            }
+            AppAction::ArtifactsExpandRow => {
+                self.set_selected_expanded(true)?;
+            }
+            AppAction::ArtifactsCollapseRow => {
+                self.set_selected_expanded(false)?;
+            }
             AppAction::ArtifactsRemoveRow => {
-                self.remove_path()?;
+                if let Some(action) = self.remove_selected()? {
+                    return Ok(Some(action));
+                }
             }
             AppAction::ArtifactsSortByPath => {
                 self.sort_by_path();
@@ -76,6 +146,13 @@ impl Artifacts {
             AppAction::ArtifactsSortBySize => {
                 self.sort_by_size();
             }
+            AppAction::ArtifactsSortByStaleness => {
+                self.sort_by_staleness();
+            }
+            AppAction::ArtifactsToggleRecommended => {
+                self.recommended = !self.recommended;
+                self.table_state.select_first();
+            }
             _ => {}
         };
         Ok(Some(AppAction::Render))
@@ -86,112 +163,320 @@ impl Artifacts {
     fn sort_by_path(&mut self) {
         self.path_order_descending = !self.path_order_descending;
         if let Ok(mut rows) = self.rows.write() {
-            if self.path_order_descending {
-                rows.sort_by_key(|row| std::cmp::Reverse(row.path_string()));
-            } else {
-                rows.sort_by_key(|row| row.path_string());
-            }
+            sort_rows(
+                &mut rows,
+                self.path_order_descending,
+                |row| Some(row.path_string()),
+                |members| {
+                    members
+                        .first()
+                        .and_then(|row| row.workspace.clone())
+                        .map(|root| root.display().to_string())
+                },
+            );
         }
     }
 
     fn sort_by_last_modified(&mut self) {
         self.last_modified_order_descending = !self.last_modified_order_descending;
         if let Ok(mut rows) = self.rows.write() {
-            if self.last_modified_order_descending {
-                rows.sort_by_key(|row| std::cmp::Reverse(row.last_modified()));
-            } else {
-                rows.sort_by_key(|row| row.last_modified());
-            }
+            sort_rows(
+                &mut rows,
+                self.last_modified_order_descending,
+                |row| row.last_modified().map(Reverse),
+                |members| workspace_last_modified(members).map(Reverse),
+            );
         }
     }
 
     fn sort_by_size(&mut self) {
         self.size_order_descending = !self.size_order_descending;
         if let Ok(mut rows) = self.rows.write() {
-            if self.size_order_descending {
-                rows.sort_by_key(|row| std::cmp::Reverse(row.size()));
-            } else {
-                rows.sort_by_key(|row| row.size());
-            }
+            sort_rows(
+                &mut rows,
+                self.size_order_descending,
+                |row| row.size(),
+                workspace_size,
+            );
         }
     }
 
-    fn insert_row(&mut self, row: FolderInfo) {
+    fn sort_by_staleness(&mut self) {
+        self.staleness_order_descending = !self.staleness_order_descending;
+        let (config, now) = (&self.config.staleness, SystemTime::now());
         if let Ok(mut rows) = self.rows.write() {
-            rows.push(row);
+            sort_rows(
+                &mut rows,
+                self.staleness_order_descending,
+                |row| staleness::score(row, config, now),
+                |members| workspace_staleness(members, config, now),
+            );
         }
     }
 
-    fn remove_path(&mut self) -> Result<()> {
-        if let Some(index) = self.table_state.selected() {
-            let tx = self.action_tx.clone();
-            let rows = Arc::clone(&self.rows);
+    /// Whether a row is listed. The recommended view hides rows that are
+    /// not recommended for deletion once they are enriched.
+    fn is_shown(&self, row: &FolderInfo, now: SystemTime) -> bool {
+        !self.recommended
+            || !row.is_enriched()
+            || row.removal_status != ProcessStatus::Pending
+            || staleness::is_recommended(row, &self.config.staleness, now)
+    }
 
-            let (id, path, status) = {
-                let data = rows.read()?;
-                let row = &data[index];
-                (row.id, row.path.clone(), row.removal_status)
-            };
+    pub fn entries(&self, rows: &[FolderInfo]) -> Vec<TableEntry> {
+        let now = SystemTime::now();
+        let shown = rows
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| self.is_shown(row, now))
+            .collect::<Vec<_>>();
 
-            if status != ProcessStatus::Pending {
-                return Ok(());
+        let mut workspaces: HashMap<&Path, Vec<usize>> = HashMap::new();
+        for (index, row) in &shown {
+            if let Some(root) = &row.workspace {
+                workspaces.entry(root).or_default().push(*index);
             }
+        }
 
-            {
-                let mut data = rows.write()?;
-                if let Some(row) = data.iter_mut().find(|r| r.id == id) {
-                    row.removal_status = ProcessStatus::InProgress;
-                    let _ = tx.send(AppAction::Render);
+        let mut seen = HashSet::new();
+        let mut entries = Vec::new();
+        for (index, row) in shown {
+            let workspace = row
+                .workspace
+                .as_deref()
+                .filter(|root| workspaces[root].len() > 1);
+            match workspace {
+                None => entries.push(TableEntry::Folder(index)),
+                Some(root) if seen.insert(root) => {
+                    let members = workspaces[root].clone();
+                    let expanded = self.expanded_workspaces.contains(root);
+                    entries.push(TableEntry::Workspace {
+                        root: root.to_path_buf(),
+                        members: members.clone(),
+                        expanded,
+                    });
+                    if expanded {
+                        entries.extend(members.into_iter().map(TableEntry::Member));
+                    }
                 }
+                Some(_) => {}
             }
+        }
+        entries
+    }
 
-            tokio::task::spawn_blocking(move || match utils::fs::remove_path(&path) {
-                Ok(_) => {
-                    Self::update_removal_status(&rows, id, ProcessStatus::Completed);
-                    let _ = tx.send(AppAction::Render);
+    fn selected_entry(&self) -> Result<Option<(TableEntry, Vec<FolderInfo>)>> {
+        let Some(index) = self.table_state.selected() else {
+            return Ok(None);
+        };
+        let rows = self.rows.read()?;
+        let entry = self.entries(&rows).into_iter().nth(index);
+        Ok(entry.map(|entry| {
+            let members = match &entry {
+                TableEntry::Folder(i) | TableEntry::Member(i) => vec![rows[*i].clone()],
+                TableEntry::Workspace { members, .. } => {
+                    members.iter().map(|i| rows[*i].clone()).collect()
                 }
-                Err(err) => {
-                    Self::update_removal_status(&rows, id, ProcessStatus::Failed);
-                    let _ = tx.send(AppAction::Error(format!("Failed to remove path: {err}")));
-                    let _ = tx.send(AppAction::Render);
+            };
+            (entry, members)
+        }))
+    }
+
+    fn set_selected_expanded(&mut self, expanded: bool) -> Result<()> {
+        let root = match self.selected_entry()? {
+            Some((TableEntry::Workspace { root, .. }, _)) => root,
+            Some((TableEntry::Member(_), members)) if !expanded => {
+                match members.first().and_then(|row| row.workspace.clone()) {
+                    Some(root) => root,
+                    None => return Ok(()),
                 }
-            });
-        }
+            }
+            _ => return Ok(()),
+        };
 
+        if expanded {
+            self.expanded_workspaces.insert(root);
+        } else {
+            self.expanded_workspaces.remove(&root);
+            let rows = self.rows.read()?;
+            let position = self.entries(&rows).iter().position(
+                |entry| matches!(entry, TableEntry::Workspace { root: r, .. } if *r == root),
+            );
+            self.table_state.select(position);
+        }
         Ok(())
     }
 
-    fn update_removal_status(
-        rows: &Arc<RwLock<Vec<FolderInfo>>>,
-        id: Uuid,
-        new_status: ProcessStatus,
-    ) {
-        if let Ok(mut data) = rows.write()
-            && let Some(row) = data.iter_mut().find(|r| r.id == id)
-        {
-            row.removal_status = new_status;
+    fn insert_row(&mut self, row: FolderInfo) {
+        self.scheduler.schedule(&row);
+        if let Some(live) = &self.live {
+            live.watch(&row);
+        }
+        self.generation += 1;
+        if let Ok(mut rows) = self.rows.write() {
+            match rows.iter_mut().find(|existing| existing.path == row.path) {
+                Some(existing) => *existing = row,
+                None => rows.push(row),
+            }
+        }
+    }
+
+    /// Removes a cached row whose folder no longer turned up in the scan.
+    /// The selection stays on the same entry, moving up with it when a row
+    /// above is dropped.
+    fn drop_row(&mut self, path: &Path) {
+        self.generation += 1;
+        let Ok(mut rows) = self.rows.write() else {
+            return;
+        };
+        let selected = self.table_state.selected().and_then(|index| {
+            let entry = self.entries(&rows).into_iter().nth(index)?;
+            Some((index, entry_path(&entry, &rows).to_path_buf()))
+        });
+        rows.retain(|row| row.path != path || row.removal_status != ProcessStatus::Pending);
+        let Some((index, selected)) = selected else {
+            return;
+        };
+        let entries = self.entries(&rows);
+        let index = entries
+            .iter()
+            .position(|entry| entry_path(entry, &rows) == selected)
+            .unwrap_or_else(|| index.min(entries.len().saturating_sub(1)));
+        drop(rows);
+        self.table_state
+            .select((!entries.is_empty()).then_some(index));
+    }
+
+    fn prioritize_visible(&self, height: usize) {
+        if let Ok(rows) = self.rows.read() {
+            let visible = self
+                .entries(&rows)
+                .into_iter()
+                .skip(self.table_state.offset())
+                .take(height)
+                .flat_map(|entry| match entry {
+                    TableEntry::Folder(index) | TableEntry::Member(index) => vec![index],
+                    TableEntry::Workspace { members, .. } => members,
+                })
+                .map(|index| rows[index].id);
+            self.scheduler.prioritize(visible);
         }
     }
 
-    pub fn load_data(&self) {
+    pub fn remove(&self, folder: &FolderInfo) -> Result<()> {
+        let removal = Removal::for_folder(folder, &self.config);
+        remove_folder(&self.rows, &self.action_tx, folder, removal)
+    }
+
+    /// Removes the selected rows. Protected rows are kept under
+    /// [`GitProtection::Block`], with a note on how many, and returned for
+    /// confirmation under [`GitProtection::Confirm`].
+    fn remove_selected(&mut self) -> Result<Option<AppAction>> {
+        self.skipped = None;
+        let Some((_, members)) = self.selected_entry()? else {
+            return Ok(None);
+        };
+        let protection = self.config.protect.git;
+        let (protected, members): (Vec<_>, Vec<_>) = members.into_iter().partition(|row| {
+            protection != GitProtection::Off
+                && row.removal_status == ProcessStatus::Pending
+                && row.is_protected()
+        });
+        for row in &members {
+            self.remove(row)?;
+        }
+        match protection {
+            GitProtection::Confirm if !protected.is_empty() => {
+                return Ok(Some(AppAction::ProtectedRemovalRequest(protected)));
+            }
+            GitProtection::Block if !protected.is_empty() => {
+                let folders = match protected.len() {
+                    1 => String::from("1 folder"),
+                    count => format!("{count} folders"),
+                };
+                self.skipped = Some(format!(
+                    "kept {folders} of repositories with local work (protect.git = \"block\")"
+                ));
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    /// Shows the cached results for the current directory right away, then
+    /// re-scans it and only replaces rows whose fingerprint changed.
+    pub fn load_data(&mut self) {
+        let Ok(root) = std::env::current_dir() else {
+            return;
+        };
+        let cache = if self.config.scan.cache {
+            ScanCache::load(&root, self.config.sizing.age)
+        } else {
+            ScanCache::default()
+        };
+        if self.config.scan.watch {
+            self.live = LiveScan::start(
+                root.clone(),
+                self.config.clone(),
+                self.rows.clone(),
+                self.action_tx.clone(),
+            );
+        }
+        for row in cache.rows() {
+            self.insert_row(row);
+        }
+        self.root = Some(root.clone());
+
         let tx_action_clone = self.action_tx.clone();
+        let config = self.config.clone();
         tokio::spawn(async move {
             let (tx_info, mut rx_info) = mpsc::unbounded_channel::<FolderInfo>();
             tokio::task::spawn_blocking(move || {
-                scan_current_dir(tx_info);
+                find_target_dirs(root, config, tx_info);
             });
+            let mut seen = HashSet::new();
             while let Some(row) = rx_info.recv().await {
-                let _ = tx_action_clone.send(AppAction::ArtifactsInsertRow(row));
+                seen.insert(row.path.clone());
+                if cache.is_valid(&row) != Some(true) {
+                    let _ = tx_action_clone.send(AppAction::ArtifactsInsertRow(row));
+                }
+            }
+            for path in cache.paths().filter(|path| !seen.contains(*path)) {
+                let _ = tx_action_clone.send(AppAction::ArtifactsDropRow(path.to_path_buf()));
             }
         });
     }
+
+    pub fn save_cache(&self) {
+        let Some(root) = &self.root else {
+            return;
+        };
+        if !self.config.scan.cache {
+            return;
+        }
+        if let Ok(rows) = self.rows.read()
+            && let Err(err) = ScanCache::save(root, self.config.sizing.age, &rows)
+        {
+            log::warn!("failed to save scan cache: {err}");
+        }
+    }
 }
 
 impl Artifacts {
-    pub fn releasable_space(&self) -> Result<u64> {
+    pub fn releasable_by_mount(&self) -> Result<MountSizes> {
         let rows = self.rows.read()?;
-        let size = rows.iter().filter_map(|row| row.size()).sum::<u64>();
-        Ok(size)
+        let key = (
+            self.generation,
+            rows.iter().filter(|row| row.dir_size().is_some()).count(),
+        );
+        if let Some((cached_key, mounts)) = self.releasable_cache.borrow().as_ref()
+            && *cached_key == key
+        {
+            return Ok(mounts.clone());
+        }
+        let mounts = releasable_by_mount(rows.iter());
+        *self.releasable_cache.borrow_mut() = Some((key, mounts.clone()));
+        Ok(mounts)
     }
 
     pub fn saved_space(&self) -> Result<u64> {
@@ -199,14 +484,268 @@ impl Artifacts {
         let size = rows
             .iter()
             .filter(|row| row.removal_status == ProcessStatus::Completed)
-            .filter_map(|row| row.size())
+            .filter_map(|row| row.exclusive_size())
             .sum::<u64>();
         Ok(size)
     }
 }
 
+pub(crate) fn remove_folder(
+    rows: &Arc<RwLock<Vec<FolderInfo>>>,
+    tx: &UnboundedSender<AppAction>,
+    folder: &FolderInfo,
+    removal: Removal,
+) -> Result<()> {
+    let (tx, rows) = (tx.clone(), Arc::clone(rows));
+    let id = folder.id;
+
+    if folder.removal_status != ProcessStatus::Pending {
+        return Ok(());
+    }
+
+    {
+        let mut data = rows.write()?;
+        if let Some(row) = data.iter_mut().find(|r| r.id == id) {
+            row.removal_status = ProcessStatus::InProgress;
+            let _ = tx.send(AppAction::Render);
+        }
+    }
+
+    let folder = folder.clone();
+    tokio::task::spawn_blocking(move || match removal.run(&folder) {
+        Ok(_) => {
+            update_removal_status(&rows, id, ProcessStatus::Completed, None);
+            let _ = tx.send(AppAction::Render);
+        }
+        Err(err) => {
+            update_removal_status(&rows, id, ProcessStatus::Failed, Some(err.to_string()));
+            let _ = tx.send(AppAction::Error(format!("Failed to remove path: {err}")));
+            let _ = tx.send(AppAction::Render);
+        }
+    });
+
+    Ok(())
+}
+
+fn update_removal_status(
+    rows: &Arc<RwLock<Vec<FolderInfo>>>,
+    id: Uuid,
+    new_status: ProcessStatus,
+    error: Option<String>,
+) {
+    if let Ok(mut data) = rows.write()
+        && let Some(row) = data.iter_mut().find(|r| r.id == id)
+    {
+        row.removal_status = new_status;
+        row.removal_error = error;
+    }
+}
+
+fn sort_rows<K: Ord + Clone>(
+    rows: &mut [FolderInfo],
+    descending: bool,
+    key: impl Fn(&FolderInfo) -> K,
+    workspace_key: impl Fn(&[&FolderInfo]) -> K,
+) {
+    let mut workspaces: HashMap<PathBuf, Vec<&FolderInfo>> = HashMap::new();
+    for row in rows.iter() {
+        if let Some(root) = &row.workspace {
+            workspaces.entry(root.clone()).or_default().push(row);
+        }
+    }
+    let workspace_keys: HashMap<PathBuf, K> = workspaces
+        .into_iter()
+        .map(|(root, members)| (root, workspace_key(&members)))
+        .collect();
+
+    rows.sort_by_cached_key(|row| {
+        let group_key = match &row.workspace {
+            Some(root) => workspace_keys[root].clone(),
+            None => key(row),
+        };
+        (group_key, row.workspace.clone(), key(row))
+    });
+    if descending {
+        rows.reverse();
+    }
+}
+
+fn workspace_size(members: &[&FolderInfo]) -> Option<u64> {
+    members.iter().map(|row| row.size()).sum()
+}
+
+fn workspace_exclusive_size(members: &[&FolderInfo]) -> Option<u64> {
+    let sizes = members
+        .iter()
+        .map(|row| row.dir_size())
+        .collect::<Option<Vec<_>>>()?;
+    Some(releasable_size(sizes))
+}
+
+fn workspace_last_modified(members: &[&FolderInfo]) -> Option<SystemTime> {
+    members
+        .iter()
+        .map(|row| row.last_modified())
+        .collect::<Option<Vec<_>>>()?
+        .into_iter()
+        .max()
+}
+
+fn workspace_staleness(
+    members: &[&FolderInfo],
+    config: &StalenessConfig,
+    now: SystemTime,
+) -> Option<u8> {
+    members
+        .iter()
+        .map(|row| staleness::score(row, config, now))
+        .collect::<Option<Vec<_>>>()?
+        .into_iter()
+        .min()
+}
+
+fn workspace_status(members: &[&FolderInfo]) -> ProcessStatus {
+    let any = |status| members.iter().any(|row| row.removal_status == status);
+    if any(ProcessStatus::InProgress) {
+        ProcessStatus::InProgress
+    } else if any(ProcessStatus::Failed) {
+        ProcessStatus::Failed
+    } else if members
+        .iter()
+        .all(|row| row.removal_status == ProcessStatus::Completed)
+    {
+        ProcessStatus::Completed
+    } else {
+        ProcessStatus::Pending
+    }
+}
+
+/// The path an entry stands for: its folder or its workspace root.
+fn entry_path<'a>(entry: &'a TableEntry, rows: &'a [FolderInfo]) -> &'a Path {
+    match entry {
+        TableEntry::Folder(index) | TableEntry::Member(index) => &rows[*index].path,
+        TableEntry::Workspace { root, .. } => root,
+    }
+}
+
+fn details_span(folder: &FolderInfo) -> Span<'static> {
+    match &folder.details {
+        Some(details) => Span::styled(
+            format!(" ({details})"),
+            Style::default().fg(Color::DarkGray),
+        ),
+        None => Span::raw(""),
+    }
+}
+
+/// The apparent size, when the files take up less space than that on disk
+/// (sparse or compressed files). Block rounding the other way is not worth
+/// a note.
+fn apparent_size_span(folder: &FolderInfo) -> Span<'static> {
+    match (folder.apparent_size(), folder.size()) {
+        (Some(apparent), Some(allocated))
+            if humanize::format_size(apparent) != humanize::format_size(allocated)
+                && apparent > allocated =>
+        {
+            Span::styled(
+                format!(" {} apparent", humanize::format_size(apparent)),
+                Style::default().fg(Color::DarkGray),
+            )
+        }
+        _ => Span::raw(""),
+    }
+}
+
+/// How many entries could not be read while sizing, if any: the size is
+/// too low by their share.
+fn size_errors_span(folder: &FolderInfo) -> Span<'static> {
+    let style = Style::default().fg(Color::LightYellow);
+    match folder.size_errors() {
+        0 => Span::raw(""),
+        1 => Span::styled(" 1 unreadable entry", style),
+        errors => Span::styled(format!(" {errors} unreadable entries"), style),
+    }
+}
+
+/// A badge for the local git work protecting a folder, if any.
+pub(crate) fn local_work_span(folder: &FolderInfo) -> Span<'static> {
+    match folder.activity().and_then(|activity| activity.local_work()) {
+        Some(work) => Span::styled(
+            format!(" [{work}]"),
+            Style::default().fg(Color::LightYellow),
+        ),
+        None => Span::raw(""),
+    }
+}
+
+/// Why removing a folder failed, if it did.
+pub(crate) fn removal_error_span(folder: &FolderInfo) -> Span<'static> {
+    match &folder.removal_error {
+        Some(error) => Span::styled(format!(" {error}"), Style::default().fg(Color::Red)),
+        None => Span::raw(""),
+    }
+}
+
+pub(crate) fn folder_row<'a>(
+    line_path: Line<'a>,
+    status: ProcessStatus,
+    last_modified: Option<String>,
+    staleness: Option<String>,
+    size: Option<String>,
+    exclusive_size: Option<String>,
+) -> Row<'a> {
+    let line_status = Line::from(vec![Span::styled(
+        match status {
+            ProcessStatus::Pending => "",
+            ProcessStatus::Completed => "Deleted",
+            ProcessStatus::Failed => "Failed",
+            ProcessStatus::InProgress => "Deleting",
+        },
+        Style::default()
+            .fg(match status {
+                ProcessStatus::Failed => Color::Red,
+                _ => Color::Green,
+            })
+            .bold(),
+    )])
+    .alignment(Alignment::Right);
+    let line_size = match size {
+        Some(size) => Line::from(size)
+            .alignment(Alignment::Right)
+            .fg(Color::LightGreen),
+        None => LoadingLine::colored_dots().alignment(Alignment::Right),
+    };
+    let line_exclusive = match exclusive_size {
+        Some(size) => Line::from(size)
+            .alignment(Alignment::Right)
+            .fg(Color::LightGreen),
+        None => LoadingLine::colored_dots().alignment(Alignment::Right),
+    };
+    let line_mod = match last_modified {
+        Some(elapsed) => Line::from(elapsed)
+            .alignment(Alignment::Right)
+            .fg(Color::LightGreen),
+        None => LoadingLine::colored_dots().alignment(Alignment::Right),
+    };
+    let line_staleness = match staleness {
+        Some(score) => Line::from(score)
+            .alignment(Alignment::Right)
+            .fg(Color::LightGreen),
+        None => LoadingLine::colored_dots().alignment(Alignment::Right),
+    };
+    Row::new(vec![
+        Cell::from(line_path),
+        Cell::from(line_status),
+        Cell::from(line_mod),
+        Cell::from(line_staleness),
+        Cell::from(line_size),
+        Cell::from(line_exclusive),
+    ])
+}
+
 pub struct ArtifacsWidget {
     pub has_focus: bool,
+    pub date_format: DateFormat,
 }
 
 impl StatefulWidget for ArtifacsWidget {
@@ -222,7 +761,26 @@ impl StatefulWidget for ArtifacsWidget {
             .border_style(Style::new().fg(block_border_color))
             .border_type(BorderType::Rounded)
             .title_bottom(" ↑ Select ↓ ".blue().bold())
-            .title_bottom(" Delete ↵ ".red().bold());
+            .title_bottom(" ← Fold → ".yellow().bold())
+            .title_bottom(" Delete ↵ ".red().bold())
+            .title_bottom(" Caches 2 ".magenta().bold())
+            .title_bottom(" History 3 ".magenta().bold());
+        let block = match state.live {
+            Some(_) => block.title(Line::from(" ● live ".green().bold()).right_aligned()),
+            None => block,
+        };
+        let block = match state.recommended {
+            true => block.title(Line::from(" recommended ".yellow().bold()).right_aligned()),
+            false => block,
+        };
+        let block = match &state.skipped {
+            Some(skipped) => block.title(Line::from(format!(" {skipped} ").light_yellow())),
+            None => block,
+        };
+        let now = SystemTime::now();
+        let staleness = |folder: &FolderInfo| {
+            staleness::score(folder, &state.config.staleness, now).map(|score| score.to_string())
+        };
 
         let table_header = Row::new(vec![
             Line::from(vec![
@@ -236,51 +794,90 @@ impl StatefulWidget for ArtifacsWidget {
                 Span::raw("odified"),
             ])
             .alignment(Alignment::Right),
+            Line::from(vec![
+                Span::raw("s"),
+                Span::styled("t", Style::default().fg(Color::Red)),
+                Span::raw("ale"),
+            ])
+            .alignment(Alignment::Right),
             Line::from(vec![
                 Span::styled("s", Style::default().fg(Color::Red)),
                 Span::raw("ize"),
             ])
             .alignment(Alignment::Right),
+            Line::from(vec![Span::raw("frees")]).alignment(Alignment::Right),
         ])
         .style(Style::default().bold());
 
         let table_rows = if let Ok(rows) = state.rows.read() {
-            rows.iter()
-                .map(|folder| {
-                    let line_path = Line::from(vec![Span::raw(folder.path_string())]);
-                    let line_status = Line::from(vec![Span::styled(
-                        match folder.removal_status {
-                            ProcessStatus::Pending => "",
-                            ProcessStatus::Completed => "Deleted",
-                            ProcessStatus::Failed => "Failed",
-                            ProcessStatus::InProgress => "Deleting",
-                        },
-                        Style::default()
-                            .fg(match folder.removal_status {
-                                ProcessStatus::Failed => Color::Red,
-                                _ => Color::Green,
-                            })
-                            .bold(),
-                    )])
-                    .alignment(Alignment::Right);
-                    let line_size = match folder.human_size() {
-                        Some(size) => Line::from(size)
-                            .alignment(Alignment::Right)
-                            .fg(Color::LightGreen),
-                        None => LoadingLine::colored_dots().alignment(Alignment::Right),
-                    };
-                    let line_mod = match folder.human_last_modified() {
-                        Some(elapsed) => Line::from(elapsed)
-                            .alignment(Alignment::Right)
-                            .fg(Color::LightGreen),
-                        None => LoadingLine::colored_dots().alignment(Alignment::Right),
-                    };
-                    Row::new(vec![
-                        Cell::from(line_path),
-                        Cell::from(line_status),
-                        Cell::from(line_mod),
-                        Cell::from(line_size),
-                    ])
+            state
+                .entries(&rows)
+                .into_iter()
+                .map(|entry| match entry {
+                    TableEntry::Folder(index) => {
+                        let folder = &rows[index];
+                        folder_row(
+                            Line::from(vec![
+                                Span::raw(folder.path_string()),
+                                details_span(folder),
+                                apparent_size_span(folder),
+                                size_errors_span(folder),
+                                local_work_span(folder),
+                                removal_error_span(folder),
+                            ]),
+                            folder.removal_status,
+                            folder.human_last_modified(self.date_format),
+                            staleness(folder),
+                            folder.human_size(),
+                            folder.human_exclusive_size(),
+                        )
+                    }
+                    TableEntry::Member(index) => {
+                        let folder = &rows[index];
+                        folder_row(
+                            Line::from(vec![
+                                Span::styled("  └ ", Style::default().fg(Color::DarkGray)),
+                                Span::raw(folder.path_string()),
+                                details_span(folder),
+                                apparent_size_span(folder),
+                                size_errors_span(folder),
+                                local_work_span(folder),
+                                removal_error_span(folder),
+                            ]),
+                            folder.removal_status,
+                            folder.human_last_modified(self.date_format),
+                            staleness(folder),
+                            folder.human_size(),
+                            folder.human_exclusive_size(),
+                        )
+                    }
+                    TableEntry::Workspace {
+                        root,
+                        members,
+                        expanded,
+                    } => {
+                        let members = members.iter().map(|i| &rows[*i]).collect::<Vec<_>>();
+                        folder_row(
+                            Line::from(vec![
+                                Span::styled(
+                                    if expanded { "▾ " } else { "▸ " },
+                                    Style::default().fg(Color::Yellow),
+                                ),
+                                Span::raw(root.display().to_string()),
+                                Span::styled(
+                                    format!(" ({} artifacts)", members.len()),
+                                    Style::default().fg(Color::DarkGray),
+                                ),
+                            ]),
+                            workspace_status(&members),
+                            workspace_last_modified(&members)
+                                .map(|time| humanize::format_date(time, self.date_format)),
+                            workspace_staleness(&members, &state.config.staleness, now)
+                                .map(|score| score.to_string()),
+                            workspace_size(&members).map(humanize::format_size),
+                            workspace_exclusive_size(&members).map(humanize::format_size),
+                        )
+                    }
                 })
                 .collect::<Vec<_>>()
         } else {
@@ -290,6 +887,8 @@ impl StatefulWidget for ArtifacsWidget {
         let table_widths = [
             Constraint::Min(0),
             Constraint::Length(10),
+            Constraint::Length(self.date_format.width()),
+            Constraint::Length(6),
             Constraint::Length(10),
             Constraint::Length(10),
         ];
@@ -305,5 +904,6 @@ impl StatefulWidget for ArtifacsWidget {
             );
 
         StatefulWidget::render(table, area, buf, &mut state.table_state);
+        state.prioritize_visible(area.height.saturating_sub(3) as usize);
     }
 }
@@ -0,0 +1,228 @@
+use crate::errors::Result;
+use crate::{
+    actions::AppAction,
+    config::StalenessConfig,
+    models::{CacheInfo, FolderInfo, ProcessStatus},
+    removal::Removal,
+    staleness,
+    ui::{
+        artifacts::{folder_row, removal_error_span, remove_folder},
+        metrics::{MountSizes, releasable_by_mount},
+    },
+    utils::{humanize::DateFormat, scanner::find_global_caches, scheduler::SizeScheduler},
+};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Rect},
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Row, StatefulWidget, Table, TableState},
+};
+use std::{
+    sync::{Arc, RwLock},
+    time::SystemTime,
+};
+use tokio::sync::mpsc::{self, UnboundedSender};
+
+pub struct Caches {
+    pub rows: Arc<RwLock<Vec<FolderInfo>>>,
+    pub table_state: TableState,
+    pub action_tx: UnboundedSender<AppAction>,
+    scheduler: SizeScheduler,
+    caches: Vec<CacheInfo>,
+    staleness: StalenessConfig,
+}
+
+impl Caches {
+    pub fn new(
+        action_tx: UnboundedSender<AppAction>,
+        scheduler: SizeScheduler,
+        caches: Vec<CacheInfo>,
+        staleness: StalenessConfig,
+    ) -> Self {
+        Self {
+            rows: Arc::new(RwLock::new(Vec::new())),
+            table_state: TableState::new(),
+            action_tx,
+            scheduler,
+            caches,
+            staleness,
+        }
+    }
+}
+
+impl Caches {
+    pub fn handle_key_event(&mut self, kev: KeyEvent) -> Option<AppAction> {
+        match kev.code {
+            KeyCode::Up => Some(AppAction::CachesSelectPreviousRow),
+            KeyCode::Down => Some(AppAction::CachesSelectNextRow),
+            KeyCode::Enter => Some(AppAction::CachesRemoveRow),
+            _ => None,
+        }
+    }
+
+    pub fn perform(&mut self, action: AppAction) -> Result<Option<AppAction>> {
+        match action {
+            AppAction::CachesSelectPreviousRow => {
+                self.table_state.select_previous();
+            }
+            AppAction::CachesSelectNextRow => {
+                self.table_state.select_next();
+            }
+            AppAction::CachesInsertRow(row) => {
+                self.insert_row(row);
+            }
+            AppAction::CachesRemoveRow => {
+                self.remove_selected()?;
+            }
+            _ => {}
+        };
+        Ok(Some(AppAction::Render))
+    }
+}
+
+impl Caches {
+    fn insert_row(&mut self, row: FolderInfo) {
+        self.scheduler.schedule(&row);
+        if let Ok(mut rows) = self.rows.write() {
+            rows.push(row);
+        }
+    }
+
+    pub fn remove(&self, folder: &FolderInfo) -> Result<()> {
+        remove_folder(&self.rows, &self.action_tx, folder, Removal::Delete)
+    }
+
+    fn remove_selected(&mut self) -> Result<()> {
+        if let Some(index) = self.table_state.selected() {
+            let row = self.rows.read()?.get(index).cloned();
+            if let Some(row) = row {
+                remove_folder(&self.rows, &self.action_tx, &row, Removal::Delete)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn load_data(&self) {
+        let tx_action_clone = self.action_tx.clone();
+        let caches = self.caches.clone();
+        tokio::spawn(async move {
+            let (tx_info, mut rx_info) = mpsc::unbounded_channel::<FolderInfo>();
+            tokio::task::spawn_blocking(move || {
+                find_global_caches(&caches, tx_info);
+            });
+            while let Some(row) = rx_info.recv().await {
+                let _ = tx_action_clone.send(AppAction::CachesInsertRow(row));
+            }
+        });
+    }
+
+    fn prioritize_visible(&self, height: usize) {
+        if let Ok(rows) = self.rows.read() {
+            let visible = rows
+                .iter()
+                .skip(self.table_state.offset())
+                .take(height)
+                .map(|row| row.id);
+            self.scheduler.prioritize(visible);
+        }
+    }
+
+    pub fn releasable_by_mount(&self) -> Result<MountSizes> {
+        let rows = self.rows.read()?;
+        Ok(releasable_by_mount(rows.iter()))
+    }
+
+    pub fn saved_space(&self) -> Result<u64> {
+        let rows = self.rows.read()?;
+        let size = rows
+            .iter()
+            .filter(|row| row.removal_status == ProcessStatus::Completed)
+            .filter_map(|row| row.exclusive_size())
+            .sum::<u64>();
+        Ok(size)
+    }
+}
+
+pub struct CachesWidget {
+    pub has_focus: bool,
+    pub date_format: DateFormat,
+}
+
+impl StatefulWidget for CachesWidget {
+    type State = Caches;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let block_border_color = match self.has_focus {
+            true => Color::LightRed,
+            false => Color::DarkGray,
+        };
+
+        let block = Block::bordered()
+            .border_style(Style::new().fg(block_border_color))
+            .border_type(BorderType::Rounded)
+            .title_bottom(" ↑ Select ↓ ".blue().bold())
+            .title_bottom(" Delete ↵ ".red().bold())
+            .title_bottom(" Artifacts 1 ".magenta().bold())
+            .title_bottom(" History 3 ".magenta().bold());
+
+        let table_header = Row::new(vec![
+            Line::from(vec![Span::raw("cache")]).alignment(Alignment::Left),
+            Line::from(vec![Span::raw("status")]).alignment(Alignment::Right),
+            Line::from(vec![Span::raw("modified")]).alignment(Alignment::Right),
+            Line::from(vec![Span::raw("stale")]).alignment(Alignment::Right),
+            Line::from(vec![Span::raw("size")]).alignment(Alignment::Right),
+            Line::from(vec![Span::raw("frees")]).alignment(Alignment::Right),
+        ])
+        .style(Style::default().bold());
+
+        let now = SystemTime::now();
+        let table_rows = if let Ok(rows) = state.rows.read() {
+            rows.iter()
+                .map(|folder| {
+                    folder_row(
+                        Line::from(vec![
+                            Span::styled(format!("{:<16}", folder.target), Style::default().bold()),
+                            Span::styled(
+                                folder.path_string(),
+                                Style::default().fg(Color::DarkGray),
+                            ),
+                            removal_error_span(folder),
+                        ]),
+                        folder.removal_status,
+                        folder.human_last_modified(self.date_format),
+                        staleness::score(folder, &state.staleness, now)
+                            .map(|score| score.to_string()),
+                        folder.human_size(),
+                        folder.human_exclusive_size(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        } else {
+            Vec::new()
+        };
+
+        let table_widths = [
+            Constraint::Min(0),
+            Constraint::Length(10),
+            Constraint::Length(self.date_format.width()),
+            Constraint::Length(6),
+            Constraint::Length(10),
+            Constraint::Length(10),
+        ];
+
+        let table = Table::new(table_rows, table_widths)
+            .header(table_header)
+            .block(block)
+            .row_highlight_style(
+                Style::default()
+                    .bg(Color::Rgb(255, 123, 123))
+                    .fg(Color::White)
+                    .bold(),
+            );
+
+        StatefulWidget::render(table, area, buf, &mut state.table_state);
+        state.prioritize_visible(area.height.saturating_sub(3) as usize);
+    }
+}
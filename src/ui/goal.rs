@@ -0,0 +1,151 @@
+use crate::{
+    actions::AppAction,
+    goal::Plan,
+    ui::artifacts::local_work_span,
+    utils::humanize::{DateFormat, format_size},
+};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Flex, Layout, Rect},
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Clear, Paragraph, Row, Table, Widget, Wrap},
+};
+
+/// The "free until" prompt: typing a goal, then confirming its plan.
+#[derive(Default)]
+pub enum GoalState {
+    #[default]
+    Closed,
+    Editing {
+        input: String,
+        error: Option<String>,
+    },
+    Confirm(Plan),
+}
+
+impl GoalState {
+    pub fn is_open(&self) -> bool {
+        !matches!(self, GoalState::Closed)
+    }
+
+    pub fn handle_key_event(&self, kev: KeyEvent) -> Option<AppAction> {
+        match self {
+            GoalState::Closed => None,
+            GoalState::Editing { .. } => match kev.code {
+                KeyCode::Esc => Some(AppAction::GoalCancel),
+                KeyCode::Enter => Some(AppAction::GoalSubmit),
+                KeyCode::Backspace => Some(AppAction::GoalBackspace),
+                KeyCode::Char(c) => Some(AppAction::GoalInput(c)),
+                _ => None,
+            },
+            GoalState::Confirm(_) => match kev.code {
+                KeyCode::Esc | KeyCode::Char('n') => Some(AppAction::GoalCancel),
+                KeyCode::Enter | KeyCode::Char('y') => Some(AppAction::GoalConfirm),
+                _ => None,
+            },
+        }
+    }
+}
+
+pub struct GoalWidget<'a> {
+    pub state: &'a GoalState,
+    pub date_format: DateFormat,
+}
+
+impl Widget for GoalWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let height = match self.state {
+            GoalState::Closed => return,
+            GoalState::Editing { .. } => 6,
+            GoalState::Confirm(plan) => plan.folders.len() as u16 + 6,
+        };
+        let [area] = Layout::vertical([Constraint::Length(height)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [area] = Layout::horizontal([Constraint::Percentage(70)])
+            .flex(Flex::Center)
+            .areas(area);
+        Clear.render(area, buf);
+
+        let block = Block::bordered()
+            .border_style(Style::new().fg(Color::LightYellow))
+            .border_type(BorderType::Rounded)
+            .title(" free until ".white().bold());
+
+        match self.state {
+            GoalState::Closed => {}
+            GoalState::Editing { input, error } => {
+                let block = block
+                    .title_bottom(" Plan ↵ ".green().bold())
+                    .title_bottom(" Cancel Esc ".red().bold());
+                let mut lines = vec![
+                    Line::from(vec![
+                        Span::raw("goal: "),
+                        Span::raw(input).bold(),
+                        "▏".into(),
+                    ]),
+                    Line::from("e.g. 50GB to release 50 GB, /home=20% or /home=100GB").dark_gray(),
+                ];
+                if let Some(error) = error {
+                    lines.push(Line::from(error.as_str()).red());
+                }
+                Paragraph::new(lines)
+                    .wrap(Wrap { trim: false })
+                    .block(block)
+                    .render(area, buf);
+            }
+            GoalState::Confirm(plan) => {
+                let summary = match plan.folders.is_empty() {
+                    true if plan.needed == 0 => String::from("goal already reached"),
+                    true => String::from("nothing to delete"),
+                    false => format!(
+                        "delete {} folders to free {}",
+                        plan.folders.len(),
+                        format_size(plan.releases)
+                    ),
+                };
+                let mut title = vec![Span::raw(format!("{}: {summary}", plan.goal)).bold()];
+                if !plan.reaches_goal() {
+                    title.push(
+                        format!(" ({} short)", format_size(plan.needed - plan.releases))
+                            .light_red(),
+                    );
+                }
+
+                let block = block
+                    .title_bottom(" Delete ↵ ".red().bold())
+                    .title_bottom(" Cancel Esc ".green().bold());
+                let header = Row::new(vec![Line::from(title)]);
+                let rows = plan.folders.iter().map(|folder| {
+                    Row::new(vec![
+                        Line::from(vec![
+                            Span::raw(folder.path_string()),
+                            local_work_span(folder),
+                        ]),
+                        Line::from(
+                            folder
+                                .human_last_modified(self.date_format)
+                                .unwrap_or_default(),
+                        )
+                        .alignment(Alignment::Right),
+                        Line::from(folder.human_exclusive_size().unwrap_or_default())
+                            .alignment(Alignment::Right),
+                    ])
+                });
+                Table::new(
+                    rows,
+                    [
+                        Constraint::Min(0),
+                        Constraint::Length(self.date_format.width()),
+                        Constraint::Length(10),
+                    ],
+                )
+                .header(header.bottom_margin(1))
+                .block(block)
+                .render(area, buf);
+            }
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use crate::{
+    actions::AppAction,
+    errors::Result,
+    utils::{
+        history::{self, Entry, Outcome, Summary},
+        humanize::{DateFormat, format_date, format_size},
+    },
+};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Layout, Rect},
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Row, StatefulWidget, Table, TableState, Widget},
+};
+use std::time::SystemTime;
+
+/// Past removals, read from the history file whenever the mode is entered.
+#[derive(Default)]
+pub struct History {
+    pub table_state: TableState,
+    entries: Vec<Entry>,
+    summary: Summary,
+}
+
+impl History {
+    pub fn load(&mut self) {
+        self.entries = history::load();
+        self.summary = Summary::new(&self.entries);
+    }
+
+    pub fn handle_key_event(&mut self, kev: KeyEvent) -> Option<AppAction> {
+        match kev.code {
+            KeyCode::Up => Some(AppAction::HistorySelectPreviousRow),
+            KeyCode::Down => Some(AppAction::HistorySelectNextRow),
+            _ => None,
+        }
+    }
+
+    pub fn perform(&mut self, action: AppAction) -> Result<Option<AppAction>> {
+        match action {
+            AppAction::HistorySelectPreviousRow => {
+                self.table_state.select_previous();
+            }
+            AppAction::HistorySelectNextRow => {
+                self.table_state.select_next();
+            }
+            _ => {}
+        };
+        Ok(Some(AppAction::Render))
+    }
+}
+
+pub struct HistoryWidget {
+    pub has_focus: bool,
+    pub date_format: DateFormat,
+}
+
+impl StatefulWidget for HistoryWidget {
+    type State = History;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let block_border_color = match self.has_focus {
+            true => Color::LightRed,
+            false => Color::DarkGray,
+        };
+
+        let summary = &state.summary;
+        let mut title = vec![
+            Span::raw(format!(
+                " freed {} in {} removals ",
+                format_size(summary.freed),
+                summary.removals
+            ))
+            .bold(),
+        ];
+        if summary.unknown_size > 0 {
+            title.push(Span::raw(format!("{} of unknown size ", summary.unknown_size)).dark_gray());
+        }
+        if summary.failures > 0 {
+            title.push(Span::raw(format!("{} failed ", summary.failures)).light_red());
+        }
+
+        let block = Block::bordered()
+            .border_style(Style::new().fg(block_border_color))
+            .border_type(BorderType::Rounded)
+            .title(Line::from(title))
+            .title_bottom(" ↑ Select ↓ ".blue().bold())
+            .title_bottom(" Artifacts 1 ".magenta().bold())
+            .title_bottom(" Caches 2 ".magenta().bold());
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let [timeline_area, targets_area] =
+            Layout::horizontal([Constraint::Min(0), Constraint::Length(40)]).areas(inner);
+
+        let timeline_header = Row::new(vec![
+            Line::from("removed"),
+            Line::from("outcome").alignment(Alignment::Right),
+            Line::from("freed").alignment(Alignment::Right),
+            Line::from("strategy"),
+            Line::from("folder"),
+        ])
+        .style(Style::default().bold());
+        let timeline_rows = state.entries.iter().map(|entry| {
+            let (outcome, color, freed) = match entry.outcome {
+                Outcome::Removed => (
+                    "Removed",
+                    Color::Green,
+                    entry
+                        .freed
+                        .map_or_else(|| String::from("unknown"), format_size),
+                ),
+                Outcome::Failed => ("Failed", Color::Red, String::new()),
+            };
+            let mut folder = vec![
+                Span::styled(format!("{:<16}", entry.target), Style::default().bold()),
+                Span::raw(entry.path.display().to_string()),
+            ];
+            if let Some(error) = &entry.error {
+                folder.push(Span::styled(
+                    format!(" {error}"),
+                    Style::default().fg(Color::Red),
+                ));
+            }
+            Row::new(vec![
+                Line::from(format_date(SystemTime::from(entry.time), self.date_format)),
+                Line::from(outcome)
+                    .alignment(Alignment::Right)
+                    .fg(color)
+                    .bold(),
+                Line::from(freed)
+                    .alignment(Alignment::Right)
+                    .fg(Color::LightGreen),
+                Line::from(entry.strategy.to_string()).fg(Color::DarkGray),
+                Line::from(folder),
+            ])
+        });
+        let timeline = Table::new(
+            timeline_rows,
+            [
+                Constraint::Length(self.date_format.width()),
+                Constraint::Length(8),
+                Constraint::Length(10),
+                Constraint::Length(8),
+                Constraint::Min(0),
+            ],
+        )
+        .header(timeline_header)
+        .row_highlight_style(
+            Style::default()
+                .bg(Color::Rgb(255, 123, 123))
+                .fg(Color::White)
+                .bold(),
+        );
+        StatefulWidget::render(timeline, timeline_area, buf, &mut state.table_state);
+
+        let targets_header = Row::new(vec![
+            Line::from("ecosystem"),
+            Line::from("count").alignment(Alignment::Right),
+            Line::from("freed").alignment(Alignment::Right),
+        ])
+        .style(Style::default().bold());
+        let targets_rows = summary.targets.iter().map(|(target, freed, removals)| {
+            Row::new(vec![
+                Line::from(target.as_str()),
+                Line::from(removals.to_string()).alignment(Alignment::Right),
+                Line::from(format_size(*freed))
+                    .alignment(Alignment::Right)
+                    .fg(Color::LightGreen),
+            ])
+        });
+        let targets = Table::new(
+            targets_rows,
+            [
+                Constraint::Min(0),
+                Constraint::Length(6),
+                Constraint::Length(10),
+            ],
+        )
+        .header(targets_header)
+        .block(
+            Block::default()
+                .borders(Borders::LEFT)
+                .border_style(Style::new().fg(Color::DarkGray)),
+        );
+        Widget::render(targets, targets_area, buf);
+    }
+}
@@ -0,0 +1,274 @@
+use crate::{
+    actions::AppAction,
+    config::Config,
+    detector::{Detector, Directory, default_detectors},
+    models::{FolderInfo, ProcessStatus},
+    utils::{
+        mounts::device,
+        scanner::{find_artifacts, find_artifacts_in},
+        watcher::{FileWatcher, platform_watcher},
+    },
+};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::{
+        Arc, RwLock,
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use tokio::sync::mpsc::{UnboundedSender, unbounded_channel};
+
+/// How long changes are collected before the table is updated.
+const DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// Artifacts with more subdirectories than this only have their top level
+/// watched, so a `node_modules` does not use up thousands of watches.
+const MAX_WATCHED_SUBDIRS: usize = 16;
+
+/// Directories outside of artifacts watched for new artifacts at most, well
+/// below the usual inotify limit of the user.
+const MAX_WATCHED_DIRS: usize = 16 * 1024;
+
+/// Keeps the artifacts table in sync with the filesystem below `root`.
+pub struct LiveScan {
+    /// Artifacts to watch, handed to a thread of their own so the UI does
+    /// not wait on reading them.
+    artifacts: mpsc::Sender<FolderInfo>,
+}
+
+impl LiveScan {
+    pub fn start(
+        root: PathBuf,
+        config: Config,
+        rows: Arc<RwLock<Vec<FolderInfo>>>,
+        action_tx: UnboundedSender<AppAction>,
+    ) -> Option<Self> {
+        let (tx, rx) = mpsc::channel();
+        let watcher = platform_watcher(tx)?;
+
+        let updater = Updater {
+            detectors: default_detectors(config.targets.clone()),
+            artifact_names: config.artifact_names().map(String::from).collect(),
+            config,
+            rows,
+            action_tx,
+            watcher: watcher.clone(),
+            watched: AtomicUsize::new(0),
+        };
+        let tree = root.clone();
+        thread::spawn(move || {
+            updater.watch_tree(&tree, device(&tree));
+            updater.run(rx);
+        });
+
+        let (artifacts, artifacts_rx) = mpsc::channel();
+        thread::spawn(move || {
+            for folder in artifacts_rx {
+                watch_artifact(watcher.as_ref(), &root, &folder);
+            }
+        });
+
+        Some(Self { artifacts })
+    }
+
+    /// Watches an artifact for growth and the directories leading to it for
+    /// new or deleted artifacts, in the background. The rest of the tree is
+    /// watched when the scan starts.
+    pub fn watch(&self, folder: &FolderInfo) {
+        let _ = self.artifacts.send(folder.clone());
+    }
+}
+
+fn watch_artifact(watcher: &dyn FileWatcher, root: &Path, folder: &FolderInfo) {
+    watcher.watch(&folder.path);
+    let dir = Directory::read(&folder.path);
+    if dir.subdirs().count() <= MAX_WATCHED_SUBDIRS {
+        for subdir in dir.subdirs() {
+            watcher.watch(&folder.path.join(&subdir.name));
+        }
+    }
+    for ancestor in folder.project.ancestors() {
+        if !ancestor.starts_with(root) {
+            break;
+        }
+        watcher.watch(ancestor);
+    }
+}
+
+struct Updater {
+    config: Config,
+    detectors: Vec<Box<dyn Detector>>,
+    /// Names of directories not descended into when watching a tree.
+    artifact_names: HashSet<String>,
+    rows: Arc<RwLock<Vec<FolderInfo>>>,
+    action_tx: UnboundedSender<AppAction>,
+    watcher: Arc<dyn FileWatcher>,
+    /// Directories watched by [`Updater::watch_tree`].
+    watched: AtomicUsize,
+}
+
+impl Updater {
+    fn run(&self, rx: Receiver<PathBuf>) {
+        let mut changed = HashSet::new();
+        let mut deadline: Option<Instant> = None;
+        loop {
+            let received = match deadline {
+                Some(deadline) => {
+                    rx.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                }
+                None => rx.recv().map_err(RecvTimeoutError::from),
+            };
+            match received {
+                Ok(path) => {
+                    changed.insert(path);
+                    deadline.get_or_insert_with(|| Instant::now() + DEBOUNCE);
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+
+            if deadline.is_some_and(|deadline| deadline <= Instant::now()) {
+                self.apply(std::mem::take(&mut changed));
+                deadline = None;
+            }
+        }
+    }
+
+    /// Updates the table for a batch of changed paths. Changed artifacts are
+    /// replaced with a copy to be sized again, whether or not they were sized
+    /// already.
+    fn apply(&self, changed: HashSet<PathBuf>) {
+        let Ok(rows) = self.rows.read() else {
+            return;
+        };
+        let known = rows
+            .iter()
+            .filter(|row| row.removal_status == ProcessStatus::Pending)
+            .cloned()
+            .collect::<Vec<_>>();
+        drop(rows);
+
+        let mut resized = HashSet::new();
+        let mut parents = HashSet::new();
+        let mut created = HashSet::new();
+        let mut dropped = HashSet::new();
+
+        for path in changed {
+            if let Some(folder) = known.iter().find(|row| path.starts_with(&row.path)) {
+                if !folder.path.exists() {
+                    dropped.insert(folder.path.clone());
+                } else if resized.insert(folder.path.clone()) {
+                    self.send(AppAction::ArtifactsInsertRow(folder.refresh()));
+                }
+            } else if path.exists() {
+                if let Some(parent) = path.parent() {
+                    parents.insert(parent.to_path_buf());
+                }
+                if path.is_dir() {
+                    created.insert(path);
+                }
+            } else {
+                dropped.extend(
+                    known
+                        .iter()
+                        .filter(|row| row.path.starts_with(&path))
+                        .map(|row| row.path.clone()),
+                );
+            }
+        }
+
+        for path in dropped {
+            self.send(AppAction::ArtifactsDropRow(path));
+        }
+
+        let mut seen = known
+            .iter()
+            .map(|row| row.path.clone())
+            .collect::<HashSet<_>>();
+        for dir in parents {
+            self.insert_new(self.rescan(&dir, false), &known, &mut seen);
+        }
+        for dir in created {
+            if seen.iter().any(|path| dir.starts_with(path)) {
+                continue;
+            }
+            self.watch_tree(&dir, device(&dir));
+            self.insert_new(self.rescan(&dir, true), &known, &mut seen);
+        }
+    }
+
+    /// Watches `dir` and the directories below it for new artifacts, the
+    /// way the scanner walks them: hidden directories are skipped like in
+    /// the scan and artifacts are left to [`LiveScan::watch`]. With
+    /// `one_file_system` the walk stays on `root_device`.
+    fn watch_tree(&self, dir: &Path, root_device: Option<u64>) {
+        let scan = &self.config.scan;
+        let watched = self.watched.fetch_add(1, Ordering::Relaxed);
+        if watched >= MAX_WATCHED_DIRS {
+            if watched == MAX_WATCHED_DIRS {
+                log::warn!(
+                    "watching {MAX_WATCHED_DIRS} directories, new artifacts elsewhere are not followed"
+                );
+            }
+            return;
+        }
+        self.watcher.watch(dir);
+        for subdir in Directory::read(dir).subdirs() {
+            let Some(name) = subdir.name.to_str() else {
+                continue;
+            };
+            let path = dir.join(name);
+            if self.artifact_names.contains(name)
+                || (name.starts_with('.') && scan.skips_hidden(name))
+                || (scan.one_file_system && device(&path) != root_device)
+            {
+                continue;
+            }
+            self.watch_tree(&path, root_device);
+        }
+    }
+
+    fn insert_new(
+        &self,
+        folders: Vec<FolderInfo>,
+        known: &[FolderInfo],
+        seen: &mut HashSet<PathBuf>,
+    ) {
+        for folder in folders {
+            if !seen.insert(folder.path.clone()) {
+                continue;
+            }
+            let workspace = folder.workspace.clone().or_else(|| {
+                known
+                    .iter()
+                    .find(|row| row.project == folder.project)
+                    .and_then(|row| row.workspace.clone())
+            });
+            self.send(AppAction::ArtifactsInsertRow(folder.workspace(workspace)));
+        }
+    }
+
+    /// Looks for artifacts in a changed directory, descending into it when
+    /// `recursive` is set.
+    fn rescan(&self, dir: &Path, recursive: bool) -> Vec<FolderInfo> {
+        let (tx, mut rx) = unbounded_channel();
+        if recursive {
+            find_artifacts(dir, &self.config.scan, &self.detectors, tx);
+        } else {
+            find_artifacts_in(dir, &self.config.scan, &self.detectors, tx);
+        }
+        let mut folders = Vec::new();
+        while let Ok(folder) = rx.try_recv() {
+            folders.push(folder);
+        }
+        folders
+    }
+
+    fn send(&self, action: AppAction) {
+        let _ = self.action_tx.send(action);
+    }
+}
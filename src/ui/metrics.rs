@@ -1,9 +1,76 @@
-use crate::utils::humanize::format_size;
+use crate::{
+    models::FolderInfo,
+    utils::{
+        fs::releasable_size,
+        humanize::format_size,
+        mounts::{self, Usage},
+    },
+};
 use ratatui::{prelude::*, widgets::*};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+/// How long the space of a mount is shown before it is read again.
+const USAGE_TTL: Duration = Duration::from_secs(5);
 
 pub struct MetricsWidget {
     pub releasable_space: u64,
     pub saved_space: u64,
+    /// Releasable bytes and usage per mount point of the listed rows.
+    pub mounts: Vec<(PathBuf, u64, Option<Usage>)>,
+}
+
+/// Free and total space per mount point, read again every [`USAGE_TTL`] and
+/// as soon as a removal freed space rather than on every frame.
+#[derive(Default)]
+pub struct UsageCache {
+    saved_space: u64,
+    usages: HashMap<PathBuf, (Instant, Option<Usage>)>,
+}
+
+impl UsageCache {
+    pub fn get(&mut self, mount: &Path, saved_space: u64) -> Option<Usage> {
+        if saved_space != self.saved_space {
+            self.saved_space = saved_space;
+            self.usages.clear();
+        }
+        let now = Instant::now();
+        match self.usages.get(mount) {
+            Some((read, usage)) if now.duration_since(*read) < USAGE_TTL => *usage,
+            _ => {
+                let usage = mounts::usage(mount);
+                self.usages.insert(mount.to_path_buf(), (now, usage));
+                usage
+            }
+        }
+    }
+}
+
+/// Releasable bytes per mount point. Rows of unknown filesystems are
+/// grouped under `None`.
+pub type MountSizes = Vec<(Option<PathBuf>, u64)>;
+
+/// Releasable bytes of the rows grouped by the filesystem they live on.
+pub fn releasable_by_mount<'a>(rows: impl IntoIterator<Item = &'a FolderInfo>) -> MountSizes {
+    let mut mounts = BTreeMap::<_, Vec<_>>::new();
+    for row in rows {
+        if let Some(size) = row.dir_size() {
+            mounts.entry(row.mount.clone()).or_default().push(size);
+        }
+    }
+    mounts
+        .into_iter()
+        .map(|(mount, sizes)| (mount, releasable_size(sizes)))
+        .collect()
+}
+
+impl MetricsWidget {
+    pub fn height(&self) -> u16 {
+        4 + self.mounts.len() as u16
+    }
 }
 
 impl Widget for MetricsWidget {
@@ -16,7 +83,7 @@ impl Widget for MetricsWidget {
             .border_type(BorderType::Rounded)
             .title(" metrics ".white().bold());
 
-        let rows = vec![
+        let mut rows = vec![
             Row::new(vec![
                 Cell::from("releasable space:"),
                 Cell::from(
@@ -35,7 +102,33 @@ impl Widget for MetricsWidget {
             ]),
         ];
 
-        let table = Table::new(rows, [Constraint::Min(20), Constraint::Length(15)]).block(block);
+        rows.extend(self.mounts.iter().map(|(mount, releasable, usage)| {
+            let usage = match usage {
+                Some(Usage { free, total }) => {
+                    format!("{} free of {}", format_size(*free), format_size(*total))
+                }
+                None => String::from("unknown usage"),
+            };
+            Row::new(vec![
+                Cell::from(format!("{}:", mount.display())),
+                Cell::from(
+                    Line::from(format_size(*releasable))
+                        .fg(Color::Green)
+                        .alignment(Alignment::Right),
+                ),
+                Cell::from(Line::from(usage).fg(Color::DarkGray)),
+            ])
+        }));
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Min(20),
+                Constraint::Length(15),
+                Constraint::Length(30),
+            ],
+        )
+        .block(block);
 
         Widget::render(table, area, buf);
     }
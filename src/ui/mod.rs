@@ -1,4 +1,9 @@
 pub mod app;
 pub mod artifacts;
+pub mod caches;
+pub mod goal;
+pub mod history;
+mod live;
 pub mod loading;
 mod metrics;
+pub mod protected;
@@ -0,0 +1,49 @@
+use crate::{models::FolderInfo, ui::artifacts::local_work_span};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Flex, Layout, Rect},
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Clear, Paragraph, Widget},
+};
+
+/// Asks once more before deleting folders of repositories with local work.
+pub struct ProtectedWidget<'a> {
+    pub folders: &'a [FolderInfo],
+}
+
+impl Widget for ProtectedWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if self.folders.is_empty() {
+            return;
+        }
+        let height = self.folders.len() as u16 + 4;
+        let [area] = Layout::vertical([Constraint::Length(height)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [area] = Layout::horizontal([Constraint::Percentage(70)])
+            .flex(Flex::Center)
+            .areas(area);
+        Clear.render(area, buf);
+
+        let block = Block::bordered()
+            .border_style(Style::new().fg(Color::LightRed))
+            .border_type(BorderType::Rounded)
+            .title(" protected ".white().bold())
+            .title_bottom(" Delete anyway y ".red().bold())
+            .title_bottom(" Keep n ".green().bold());
+
+        let mut lines = vec![
+            Line::from("These folders belong to repositories with local work:").bold(),
+            Line::default(),
+        ];
+        lines.extend(self.folders.iter().map(|folder| {
+            Line::from(vec![
+                Span::raw(folder.path_string()),
+                local_work_span(folder),
+            ])
+        }));
+
+        Paragraph::new(lines).block(block).render(area, buf);
+    }
+}
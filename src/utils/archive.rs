@@ -0,0 +1,255 @@
+use crate::{
+    config::{ArchiveConfig, Compression},
+    errors::{Error, Result},
+    models::FolderInfo,
+    utils::fs::remove_path,
+};
+use flate2::{read::GzDecoder, write::GzEncoder};
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Reverse,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Name of the first entry of every archive, describing where it came from.
+const MANIFEST: &str = "vaporz-manifest.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Manifest {
+    /// The archived folder.
+    pub path: PathBuf,
+    pub target: String,
+    pub archived: SystemTime,
+    /// Apparent size of the folder when it was archived.
+    pub size: Option<u64>,
+}
+
+/// An archive in the archive directory.
+#[derive(Debug, Clone)]
+pub struct Archive {
+    pub file: PathBuf,
+    pub manifest: Manifest,
+}
+
+/// Streams `folder` into a new tarball in the archive directory, then
+/// deletes it. Returns the tarball.
+pub fn archive(folder: &FolderInfo, config: &ArchiveConfig) -> Result<PathBuf> {
+    let dir = config.dir();
+    fs::create_dir_all(&dir)?;
+
+    let extension = match config.compression {
+        Compression::None => "tar",
+        Compression::Gzip => "tar.gz",
+    };
+    let file = dir.join(format!("{}.{extension}", folder.unique_name()));
+    let manifest = Manifest {
+        path: folder.path.clone(),
+        target: folder.target.clone(),
+        archived: SystemTime::now(),
+        size: folder.apparent_size(),
+    };
+
+    // Written under a temporary name so an interrupted run never leaves a
+    // truncated archive that looks complete.
+    let partial = PathBuf::from(format!("{}.partial", file.display()));
+    if let Err(err) = write(&partial, &folder.path, &manifest, config.compression) {
+        let _ = fs::remove_file(&partial);
+        return Err(err.into());
+    }
+    fs::rename(&partial, &file)?;
+    remove_path(&folder.path)?;
+    Ok(file)
+}
+
+fn write(file: &Path, dir: &Path, manifest: &Manifest, compression: Compression) -> io::Result<()> {
+    let out = BufWriter::new(File::create(file)?);
+    match compression {
+        Compression::None => append(out, dir, manifest)?.flush(),
+        Compression::Gzip => {
+            let encoder = GzEncoder::new(out, flate2::Compression::default());
+            append(encoder, dir, manifest)?.finish()?.flush()
+        }
+    }
+}
+
+fn append<W: Write>(out: W, dir: &Path, manifest: &Manifest) -> io::Result<W> {
+    let mut builder = tar::Builder::new(out);
+    builder.follow_symlinks(false);
+
+    let json = serde_json::to_vec_pretty(manifest)?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(json.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(
+        manifest
+            .archived
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |age| age.as_secs()),
+    );
+    header.set_cksum();
+    builder.append_data(&mut header, MANIFEST, json.as_slice())?;
+
+    let name = dir.file_name().ok_or(io::ErrorKind::InvalidInput)?;
+    builder.append_dir_all(name, dir)?;
+    builder.into_inner()
+}
+
+/// Archives in the archive directory, newest first.
+pub fn list(config: &ArchiveConfig) -> Vec<Archive> {
+    let Ok(entries) = fs::read_dir(config.dir()) else {
+        return Vec::new();
+    };
+    let mut archives = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|file| compression(file).is_some())
+        .filter_map(|file| match read_manifest(&file) {
+            Ok(manifest) => Some(Archive { file, manifest }),
+            Err(err) => {
+                log::warn!("skipping archive {}: {err}", file.display());
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    archives.sort_by_key(|archive| Reverse(archive.manifest.archived));
+    archives
+}
+
+/// Unpacks an archive back to where it came from and deletes it.
+pub fn restore(archive: &Archive) -> Result<()> {
+    let path = &archive.manifest.path;
+    if path.exists() {
+        return Err(Error::Archive(format!("{} already exists", path.display())));
+    }
+    let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+        return Err(Error::Archive(format!(
+            "cannot restore to {}",
+            path.display()
+        )));
+    };
+    fs::create_dir_all(parent)?;
+
+    // Unpacked next to the destination first, so a failure halfway does not
+    // leave a partial folder that looks restored.
+    let staging = parent.join(format!(".{}.restoring", name.to_string_lossy()));
+    let unpacked = unpack(&archive.file, &staging).and_then(|_| {
+        fs::rename(staging.join(name), path)?;
+        remove_path(&staging)
+    });
+    if let Err(err) = unpacked {
+        let _ = remove_path(&staging);
+        return Err(err);
+    }
+    fs::remove_file(&archive.file)?;
+    Ok(())
+}
+
+fn unpack(file: &Path, dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir)?;
+    let mut tar = open(file)?;
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        if entry.path()?.as_ref() == Path::new(MANIFEST) {
+            continue;
+        }
+        entry.unpack_in(dir)?;
+    }
+    Ok(())
+}
+
+fn read_manifest(file: &Path) -> Result<Manifest> {
+    let mut tar = open(file)?;
+    let mut entry = tar
+        .entries()?
+        .next()
+        .ok_or_else(|| Error::Archive(String::from("empty archive")))??;
+    if entry.path()?.as_ref() != Path::new(MANIFEST) {
+        return Err(Error::Archive(String::from("no manifest")));
+    }
+    let mut json = Vec::new();
+    entry.read_to_end(&mut json)?;
+    serde_json::from_slice(&json).map_err(|err| Error::Archive(err.to_string()))
+}
+
+fn open(file: &Path) -> Result<tar::Archive<Box<dyn Read>>> {
+    let reader = BufReader::new(File::open(file)?);
+    let reader: Box<dyn Read> = match compression(file) {
+        Some(Compression::Gzip) => Box::new(GzDecoder::new(reader)),
+        _ => Box::new(reader),
+    };
+    Ok(tar::Archive::new(reader))
+}
+
+fn compression(file: &Path) -> Option<Compression> {
+    let name = file.file_name()?.to_str()?;
+    if name.ends_with(".tar.gz") {
+        Some(Compression::Gzip)
+    } else if name.ends_with(".tar") {
+        Some(Compression::None)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::fs::calculate_dir_size;
+    use uuid::Uuid;
+
+    #[cfg(unix)]
+    #[test]
+    fn restores_what_was_archived() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = std::env::temp_dir().join(format!("vaporz-archive-{}", Uuid::new_v4().simple()));
+        let folder = root.join("app/target");
+        fs::create_dir_all(folder.join("debug/deps")).unwrap();
+        fs::write(folder.join("debug/app"), "binary").unwrap();
+        fs::write(folder.join("debug/deps/lib.rlib"), "library").unwrap();
+        fs::set_permissions(folder.join("debug/app"), fs::Permissions::from_mode(0o751)).unwrap();
+        fs::set_permissions(folder.join("debug/deps"), fs::Permissions::from_mode(0o700)).unwrap();
+
+        let info = FolderInfo::new(folder.clone())
+            .project(root.join("app"))
+            .target("Rust");
+        info.set_size(calculate_dir_size(&folder, false));
+        let config = ArchiveConfig {
+            dir: Some(root.join("archive").display().to_string()),
+            compression: Compression::Gzip,
+        };
+        archive(&info, &config).unwrap();
+        assert!(!folder.exists());
+
+        let archives = list(&config);
+        assert_eq!(archives.len(), 1);
+        assert_eq!(archives[0].manifest.path, folder);
+        assert_eq!(archives[0].manifest.target, "Rust");
+        assert_eq!(archives[0].manifest.size, info.apparent_size());
+
+        restore(&archives[0]).unwrap();
+        assert!(list(&config).is_empty());
+        assert_eq!(
+            fs::read_to_string(folder.join("debug/app")).unwrap(),
+            "binary"
+        );
+        assert_eq!(
+            fs::read_to_string(folder.join("debug/deps/lib.rlib")).unwrap(),
+            "library"
+        );
+        let mode = |path: &str| {
+            fs::metadata(folder.join(path))
+                .unwrap()
+                .permissions()
+                .mode()
+                & 0o777
+        };
+        assert_eq!(mode("debug/app"), 0o751);
+        assert_eq!(mode("debug/deps"), 0o700);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -1,53 +1,257 @@
 use crate::errors::Result;
-use std::{fs, path::Path};
+use rayon::prelude::*;
+use std::{
+    collections::HashMap,
+    fs::{self, Metadata},
+    io,
+    ops::Add,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
 
-pub fn last_modified<P: AsRef<Path>>(path: P) -> Option<u64> {
-    fs::metadata(path)
-        .ok()?
-        .modified()
+pub fn modified<P: AsRef<Path>>(path: P) -> Option<SystemTime> {
+    fs::metadata(path).ok()?.modified().ok()
+}
+
+pub fn newest_modified(dir: &Path, skip: &(dyn Fn(&Path) -> bool + Sync)) -> Option<SystemTime> {
+    fs::read_dir(dir)
         .ok()?
-        .elapsed()
-        .ok()
-        .map(|d| d.as_secs())
+        .par_bridge()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| !skip(&entry.path()))
+        .filter_map(|entry| {
+            let file_type = entry.file_type().ok()?;
+            if file_type.is_dir() {
+                newest_modified(&entry.path(), skip)
+            } else if file_type.is_file() {
+                entry.metadata().ok()?.modified().ok()
+            } else {
+                None
+            }
+        })
+        .max()
 }
 
-#[cfg(unix)]
-pub fn calculate_dir_size<P: AsRef<Path>>(dir: P) -> u64 {
-    use std::process::Command;
-    let cmd = Command::new("du").arg("-sk").arg(dir.as_ref()).output();
-
-    let output = if let Ok(output) = cmd {
-        output
-    } else {
-        return 0;
-    };
+type Inode = (u64, u64);
 
-    if !output.status.success() {
-        return 0;
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct LinkedFile {
+    pub links: u64,
+    pub seen: u64,
+    pub apparent: u64,
+    pub allocated: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DirSize {
+    pub apparent: u64,
+    pub allocated: u64,
+    pub exclusive: u64,
+    pub errors: u64,
+    pub newest_modified: Option<SystemTime>,
+    unlinked: u64,
+    linked: HashMap<Inode, LinkedFile>,
+}
+
+impl DirSize {
+    fn new(walk: SizeWalk) -> Self {
+        let linked = walk.linked.values();
+        Self {
+            apparent: walk.apparent + linked.clone().map(|file| file.apparent).sum::<u64>(),
+            allocated: walk.allocated + linked.clone().map(|file| file.allocated).sum::<u64>(),
+            exclusive: walk.allocated
+                + linked
+                    .filter(|file| file.seen >= file.links)
+                    .map(|file| file.allocated)
+                    .sum::<u64>(),
+            errors: walk.errors,
+            newest_modified: walk.newest_modified,
+            unlinked: walk.allocated,
+            linked: walk.linked,
+        }
     }
+}
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    stdout
-        .split_whitespace()
-        .next()
-        .and_then(|s| s.parse::<u64>().ok())
-        .map(|kb| kb * 1024)
-        .unwrap_or(0)
+impl DirSize {
+    /// Rebuilds a size from persisted totals. Hardlink details are not kept,
+    /// so the exclusive bytes are treated as unshared.
+    pub fn restored(
+        apparent: u64,
+        allocated: u64,
+        exclusive: u64,
+        errors: u64,
+        newest_modified: Option<SystemTime>,
+    ) -> Self {
+        Self {
+            apparent,
+            allocated,
+            exclusive,
+            errors,
+            newest_modified,
+            unlinked: exclusive,
+            linked: HashMap::new(),
+        }
+    }
 }
 
-#[cfg(not(unix))]
-pub fn calculate_dir_size<P: AsRef<Path>>(dir: P) -> u64 {
-    use rayon::prelude::*;
-    use std::fs;
-    use walkdir::WalkDir;
-    WalkDir::new(dir)
-        .into_iter()
-        .filter_map(|res| res.ok())
-        .filter(|entry| !entry.path_is_symlink())
-        .filter(|entry| entry.file_type().is_file())
+/// Bytes freed by deleting all of `sizes`, counting every hardlinked inode
+/// once and only when all of its links are among them.
+pub fn releasable_size<'a>(sizes: impl IntoIterator<Item = &'a DirSize>) -> u64 {
+    let mut unlinked = 0;
+    let mut linked: HashMap<Inode, LinkedFile> = HashMap::new();
+    for size in sizes {
+        unlinked += size.unlinked;
+        for (inode, file) in &size.linked {
+            linked
+                .entry(*inode)
+                .and_modify(|linked| linked.seen += file.seen)
+                .or_insert(*file);
+        }
+    }
+    unlinked
+        + linked
+            .values()
+            .filter(|file| file.seen >= file.links)
+            .map(|file| file.allocated)
+            .sum::<u64>()
+}
+
+#[derive(Default)]
+struct SizeWalk {
+    apparent: u64,
+    allocated: u64,
+    errors: u64,
+    newest_modified: Option<SystemTime>,
+    linked: HashMap<Inode, LinkedFile>,
+}
+
+impl Add for SizeWalk {
+    type Output = Self;
+
+    fn add(mut self, mut other: Self) -> Self {
+        if self.linked.len() < other.linked.len() {
+            std::mem::swap(&mut self.linked, &mut other.linked);
+        }
+        for (inode, file) in other.linked {
+            self.linked
+                .entry(inode)
+                .and_modify(|linked| linked.seen += file.seen)
+                .or_insert(file);
+        }
+        Self {
+            apparent: self.apparent + other.apparent,
+            allocated: self.allocated + other.allocated,
+            errors: self.errors + other.errors,
+            newest_modified: self.newest_modified.max(other.newest_modified),
+            linked: self.linked,
+        }
+    }
+}
+
+impl SizeWalk {
+    fn from_metadata(metadata: &Metadata) -> Self {
+        #[cfg(unix)]
+        let (allocated, linked) = {
+            use std::os::unix::fs::MetadataExt;
+            let allocated = metadata.blocks() * 512;
+            let linked = (!metadata.is_dir() && metadata.nlink() > 1).then(|| {
+                let file = LinkedFile {
+                    links: metadata.nlink(),
+                    seen: 1,
+                    apparent: metadata.len(),
+                    allocated,
+                };
+                ((metadata.dev(), metadata.ino()), file)
+            });
+            (allocated, linked)
+        };
+        #[cfg(not(unix))]
+        let (allocated, linked) = (metadata.len(), None);
+
+        let newest_modified = match metadata.is_dir() {
+            true => None,
+            false => metadata.modified().ok(),
+        };
+
+        match linked {
+            Some((inode, file)) => Self {
+                newest_modified,
+                linked: HashMap::from([(inode, file)]),
+                ..Default::default()
+            },
+            None => Self {
+                apparent: metadata.len(),
+                allocated,
+                newest_modified,
+                ..Default::default()
+            },
+        }
+    }
+
+    fn error(path: &Path, err: io::Error) -> Self {
+        log::warn!("Failed to read {}: {err}", path.display());
+        Self {
+            errors: 1,
+            ..Default::default()
+        }
+    }
+}
+
+/// Sizes `dir` and everything below it. With `one_file_system`, directories
+/// on other filesystems than `dir` are left out, like in the scan.
+pub fn calculate_dir_size<P: AsRef<Path>>(dir: P, one_file_system: bool) -> DirSize {
+    let path = dir.as_ref();
+    let walk = match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.is_dir() => {
+            let device = one_file_system.then(|| device(&metadata)).flatten();
+            SizeWalk::from_metadata(&metadata) + entries_size(path, device)
+        }
+        Ok(metadata) => SizeWalk::from_metadata(&metadata),
+        Err(err) => SizeWalk::error(path, err),
+    };
+    DirSize::new(walk)
+}
+
+/// Sizes the entries of `dir`, skipping directories not on `device` if set.
+fn entries_size(dir: &Path, device_filter: Option<u64>) -> SizeWalk {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) => return SizeWalk::error(dir, err),
+    };
+
+    entries
         .par_bridge()
-        .map(|entry| fs::metadata(entry.path()).map(|m| m.len()).unwrap_or(0))
-        .sum()
+        .map(|entry| {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(err) => return SizeWalk::error(dir, err),
+            };
+            match entry.metadata() {
+                Ok(metadata)
+                    if metadata.is_dir()
+                        && device_filter.is_some_and(|dev| device(&metadata) != Some(dev)) =>
+                {
+                    SizeWalk::default()
+                }
+                Ok(metadata) if metadata.is_dir() => {
+                    SizeWalk::from_metadata(&metadata) + entries_size(&entry.path(), device_filter)
+                }
+                Ok(metadata) => SizeWalk::from_metadata(&metadata),
+                Err(err) => SizeWalk::error(&entry.path(), err),
+            }
+        })
+        .reduce(SizeWalk::default, Add::add)
+}
+
+#[cfg(unix)]
+fn device(metadata: &Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.dev())
+}
+
+#[cfg(not(unix))]
+fn device(_metadata: &Metadata) -> Option<u64> {
+    None
 }
 
 pub fn remove_path(path: &Path) -> Result<()> {
@@ -58,3 +262,11 @@ pub fn remove_path(path: &Path) -> Result<()> {
     }
     Ok(())
 }
+
+/// The application's directory for history and logs in the user state
+/// directory, or the local data directory where there is none.
+pub fn state_dir() -> Option<PathBuf> {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .map(|dir| dir.join("vaporz"))
+}
@@ -0,0 +1,61 @@
+use std::{
+    path::Path,
+    process::Command,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Local work of a repository that deleting its artifacts could get in the
+/// way of.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Status {
+    /// Uncommitted or untracked changes in the working tree.
+    pub dirty: bool,
+    pub stashes: usize,
+    /// Commits on the current branch not on its upstream.
+    pub ahead: usize,
+}
+
+/// Reads the working tree, stash and upstream state. Only local refs are
+/// compared; no remote is contacted.
+pub fn status(dir: &Path) -> Option<Status> {
+    let output = git(
+        dir,
+        &["status", "--porcelain=v2", "--branch", "--show-stash"],
+    )?;
+    let mut status = Status::default();
+    for line in output.lines() {
+        if let Some(ab) = line.strip_prefix("# branch.ab ") {
+            status.ahead = ab
+                .split_whitespace()
+                .next()
+                .and_then(|ahead| ahead.trim_start_matches('+').parse().ok())
+                .unwrap_or(0);
+        } else if let Some(stashes) = line.strip_prefix("# stash ") {
+            status.stashes = stashes.trim().parse().unwrap_or(0);
+        } else if !line.starts_with('#') {
+            status.dirty = true;
+        }
+    }
+    Some(status)
+}
+
+pub fn last_commit_time(dir: &Path) -> Option<SystemTime> {
+    let secs = git(dir, &["log", "-1", "--format=%ct", "HEAD"])?
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(UNIX_EPOCH + Duration::from_secs(secs))
+}
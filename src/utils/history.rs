@@ -0,0 +1,166 @@
+use crate::{config::Strategy, errors::Result, models::FolderInfo, utils::fs::state_dir};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    sync::Mutex,
+};
+
+/// Serializes appends by concurrent removals.
+static HISTORY_LOCK: Mutex<()> = Mutex::new(());
+
+/// One removal in the history.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub time: DateTime<Local>,
+    pub path: PathBuf,
+    pub target: String,
+    /// Bytes released. Unknown when the folder was removed before it was
+    /// sized, and when the removal failed.
+    #[serde(default)]
+    pub freed: Option<u64>,
+    pub strategy: Strategy,
+    pub outcome: Outcome,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub user: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Removed,
+    Failed,
+}
+
+impl Entry {
+    pub fn new(folder: &FolderInfo, strategy: Strategy, result: &Result<()>) -> Self {
+        let (outcome, freed, error) = match result {
+            Ok(()) => (Outcome::Removed, folder.exclusive_size(), None),
+            Err(err) => (Outcome::Failed, None, Some(err.to_string())),
+        };
+        Self {
+            time: Local::now(),
+            path: folder.path.clone(),
+            target: folder.target.clone(),
+            freed,
+            strategy,
+            outcome,
+            error,
+            user: user(),
+        }
+    }
+}
+
+fn user() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| uid())
+}
+
+#[cfg(unix)]
+fn uid() -> String {
+    // SAFETY: getuid has no preconditions and cannot fail.
+    unsafe { libc::getuid() }.to_string()
+}
+
+#[cfg(not(unix))]
+fn uid() -> String {
+    String::new()
+}
+
+/// The history file: one JSON entry per line in the user state directory.
+pub fn path() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("history.jsonl"))
+}
+
+/// Appends an entry to the history. Failing to do so does not fail the
+/// removal, it is only logged.
+pub fn record(entry: &Entry) {
+    let Some(path) = path() else {
+        return;
+    };
+    let append = || -> std::io::Result<()> {
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+        let _guard = HISTORY_LOCK.lock();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)?
+            .write_all(line.as_bytes())
+    };
+    if let Err(err) = append() {
+        log::warn!("failed to write history to {}: {err}", path.display());
+    }
+}
+
+/// All entries in the history, newest first. Unreadable lines are skipped.
+pub fn load() -> Vec<Entry> {
+    let Some(content) = path().and_then(|path| fs::read_to_string(path).ok()) else {
+        return Vec::new();
+    };
+    let mut entries = content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(entry) => Some(entry),
+            Err(err) => {
+                log::warn!("skipping history entry: {err}");
+                None
+            }
+        })
+        .collect::<Vec<Entry>>();
+    entries.reverse();
+    entries
+}
+
+/// Lifetime totals of a history.
+#[derive(Debug, Clone, Default)]
+pub struct Summary {
+    /// Bytes freed by the removals of known size.
+    pub freed: u64,
+    pub removals: usize,
+    /// Removals of folders that were not sized yet.
+    pub unknown_size: usize,
+    pub failures: usize,
+    /// Bytes freed and removals per target, most freed first.
+    pub targets: Vec<(String, u64, usize)>,
+}
+
+impl Summary {
+    pub fn new(entries: &[Entry]) -> Self {
+        let mut summary = Summary::default();
+        let mut targets: HashMap<&str, (u64, usize)> = HashMap::new();
+        for entry in entries {
+            match entry.outcome {
+                Outcome::Removed => {
+                    let freed = entry.freed.unwrap_or(0);
+                    summary.freed += freed;
+                    summary.removals += 1;
+                    if entry.freed.is_none() {
+                        summary.unknown_size += 1;
+                    }
+                    let total = targets.entry(&entry.target).or_default();
+                    total.0 += freed;
+                    total.1 += 1;
+                }
+                Outcome::Failed => summary.failures += 1,
+            }
+        }
+        summary.targets = targets
+            .into_iter()
+            .map(|(target, (freed, removals))| (target.to_string(), freed, removals))
+            .collect();
+        summary
+            .targets
+            .sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        summary
+    }
+}
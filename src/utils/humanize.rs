@@ -1,3 +1,6 @@
+use chrono::{DateTime, Local, Utc};
+use std::time::SystemTime;
+
 pub fn format_size(size: u64) -> String {
     let units = ["B", "KB", "MB", "GB", "TB", "PB", "EB"];
     let mut size = size as f64;
@@ -11,6 +14,71 @@ pub fn format_size(size: u64) -> String {
     format!("{:.1} {}", size, units[unit_index])
 }
 
+/// Parses sizes like `512MB`, `50 GB`, `1.5T` or a plain byte count, in the
+/// same 1024-based units [`format_size`] prints.
+pub fn parse_size(input: &str) -> Option<u64> {
+    let input = input.trim();
+    let split = input
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(input.len());
+    let (number, unit) = input.split_at(split);
+    let number = number.parse::<f64>().ok()?;
+    let exponent = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 0,
+        "K" | "KB" | "KIB" => 1,
+        "M" | "MB" | "MIB" => 2,
+        "G" | "GB" | "GIB" => 3,
+        "T" | "TB" | "TIB" => 4,
+        "P" | "PB" | "PIB" => 5,
+        _ => return None,
+    };
+    Some((number * 1024f64.powi(exponent)) as u64)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DateFormat {
+    #[default]
+    Relative,
+    Iso8601,
+    Local,
+}
+
+impl DateFormat {
+    pub fn next(self) -> Self {
+        match self {
+            DateFormat::Relative => DateFormat::Iso8601,
+            DateFormat::Iso8601 => DateFormat::Local,
+            DateFormat::Local => DateFormat::Relative,
+        }
+    }
+
+    pub fn width(self) -> u16 {
+        match self {
+            DateFormat::Relative => 10,
+            DateFormat::Iso8601 => 20,
+            DateFormat::Local => 16,
+        }
+    }
+}
+
+pub fn format_date(time: SystemTime, format: DateFormat) -> String {
+    match format {
+        DateFormat::Relative => {
+            format_last_modified(time.elapsed().map(|d| d.as_secs()).unwrap_or_default())
+        }
+        DateFormat::Iso8601 => format_iso8601(time),
+        DateFormat::Local => DateTime::<Local>::from(time)
+            .format("%Y-%m-%d %H:%M")
+            .to_string(),
+    }
+}
+
+pub fn format_iso8601(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time)
+        .format("%Y-%m-%dT%H:%M:%SZ")
+        .to_string()
+}
+
 pub fn format_last_modified(secs: u64) -> String {
     const MINUTE: u64 = 60;
     const HOUR: u64 = MINUTE * 60;
@@ -25,3 +93,26 @@ pub fn format_last_modified(secs: u64) -> String {
 
     format!("{unit:.0}{fstring}")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sizes() {
+        assert_eq!(parse_size("0"), Some(0));
+        assert_eq!(parse_size("512"), Some(512));
+        assert_eq!(parse_size("512B"), Some(512));
+        assert_eq!(parse_size("4k"), Some(4 << 10));
+        assert_eq!(parse_size("1.5 MB"), Some(3 << 19));
+        assert_eq!(parse_size(" 50GiB "), Some(50 << 30));
+        assert_eq!(parse_size("2T"), Some(2 << 40));
+    }
+
+    #[test]
+    fn rejects_invalid_sizes() {
+        for input in ["", "GB", "-5GB", "1.2.3GB", "10XB", "10 G B"] {
+            assert_eq!(parse_size(input), None, "{input} should be invalid");
+        }
+    }
+}
@@ -1,3 +1,13 @@
+pub mod archive;
 pub mod fs;
+pub mod git;
+pub mod history;
 pub mod humanize;
+pub mod mounts;
+pub mod relocate;
+pub mod scan_cache;
 pub mod scanner;
+pub mod scheduler;
+pub mod venv;
+pub mod watcher;
+pub mod workspace;
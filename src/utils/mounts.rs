@@ -0,0 +1,62 @@
+use std::path::{Path, PathBuf};
+
+/// Free and total bytes of a filesystem, as available to unprivileged users.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Usage {
+    pub free: u64,
+    pub total: u64,
+}
+
+/// The id of the device a path lives on.
+#[cfg(unix)]
+pub fn device(path: &Path) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    std::fs::symlink_metadata(path)
+        .ok()
+        .map(|metadata| metadata.dev())
+}
+
+#[cfg(not(unix))]
+pub fn device(_path: &Path) -> Option<u64> {
+    None
+}
+
+/// The mount point of the filesystem a path lives on: its outermost ancestor
+/// on the same device.
+pub fn mount_point(path: &Path) -> Option<PathBuf> {
+    let dev = device(path)?;
+    let mut mount = path;
+    for ancestor in path.ancestors().skip(1) {
+        if device(ancestor) != Some(dev) {
+            break;
+        }
+        mount = ancestor;
+    }
+    Some(mount.to_path_buf())
+}
+
+#[cfg(unix)]
+pub fn usage(mount: &Path) -> Option<Usage> {
+    use std::{ffi::CString, mem::MaybeUninit, os::unix::ffi::OsStrExt};
+
+    let path = CString::new(mount.as_os_str().as_bytes()).ok()?;
+    let mut stat = MaybeUninit::<libc::statvfs>::uninit();
+    // SAFETY: `path` is a valid C string and `stat` is only read after
+    // statvfs reported success.
+    let stat = unsafe {
+        if libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) != 0 {
+            return None;
+        }
+        stat.assume_init()
+    };
+    let fragment = stat.f_frsize as u64;
+    Some(Usage {
+        free: stat.f_bavail as u64 * fragment,
+        total: stat.f_blocks as u64 * fragment,
+    })
+}
+
+#[cfg(not(unix))]
+pub fn usage(_mount: &Path) -> Option<Usage> {
+    None
+}
@@ -0,0 +1,337 @@
+use crate::{
+    config::RelocateConfig,
+    errors::{Error, Result},
+    models::FolderInfo,
+    utils::fs::remove_path,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Reverse,
+    fs::{self, File},
+    io::{self, Read},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::SystemTime,
+};
+
+/// The path map in the relocation directory, recording where every
+/// relocated folder came from.
+const PATH_MAP: &str = "vaporz-relocations.json";
+
+/// Serializes updates of the path map by concurrent relocations.
+static PATH_MAP_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Relocation {
+    /// Where the folder was.
+    pub from: PathBuf,
+    /// Where the folder is now.
+    pub to: PathBuf,
+    pub target: String,
+    pub relocated: SystemTime,
+    /// Apparent size of the folder when it was relocated.
+    pub size: Option<u64>,
+    /// Whether a symlink to `to` was left at `from`.
+    pub symlink: bool,
+}
+
+/// Moves `folder` into the relocation directory, optionally leaving a
+/// symlink behind. Returns its new location.
+pub fn relocate(folder: &FolderInfo, config: &RelocateConfig) -> Result<PathBuf> {
+    let dir = config
+        .dir()
+        .ok_or_else(|| Error::Relocate(String::from("no relocation `dir` configured")))?;
+    fs::create_dir_all(&dir)?;
+
+    let to = dir.join(folder.unique_name());
+    // The path map entry goes in first: a folder moved without one could
+    // not be found by `vaporz restore`.
+    let relocation = Relocation {
+        from: folder.path.clone(),
+        to: to.clone(),
+        target: folder.target.clone(),
+        relocated: SystemTime::now(),
+        size: folder.apparent_size(),
+        symlink: false,
+    };
+    update_path_map(&dir, |map| map.push(relocation.clone()))?;
+    if let Err(err) = move_dir(&folder.path, &to) {
+        if let Err(err) = update_path_map(&dir, |map| map.retain(|entry| entry.to != to)) {
+            log::warn!("failed to drop {} from the path map: {err}", to.display());
+        }
+        return Err(err);
+    }
+
+    if config.symlink {
+        match make_symlink(&to, &folder.path) {
+            Ok(()) => {
+                let linked = update_path_map(&dir, |map| {
+                    map.iter_mut()
+                        .filter(|entry| entry.to == to)
+                        .for_each(|entry| entry.symlink = true);
+                });
+                if let Err(err) = linked {
+                    log::warn!(
+                        "failed to record the link at {}: {err}",
+                        folder.path.display()
+                    );
+                }
+            }
+            Err(err) => log::warn!("failed to link {}: {err}", folder.path.display()),
+        }
+    }
+    Ok(to)
+}
+
+/// Relocated folders, most recent first.
+pub fn list(config: &RelocateConfig) -> Vec<Relocation> {
+    let Some(dir) = config.dir() else {
+        return Vec::new();
+    };
+    let mut relocations = read_path_map(&dir).unwrap_or_else(|err| {
+        log::warn!("failed to read the path map in {}: {err}", dir.display());
+        Vec::new()
+    });
+    relocations.sort_by_key(|relocation| Reverse(relocation.relocated));
+    relocations
+}
+
+/// Moves a relocated folder back, replacing the symlink left behind.
+pub fn restore(relocation: &Relocation, config: &RelocateConfig) -> Result<()> {
+    let from = &relocation.from;
+    let linked = fs::read_link(from).is_ok_and(|target| target == relocation.to);
+    if linked {
+        remove_symlink(from)?;
+    } else if fs::symlink_metadata(from).is_ok() {
+        return Err(Error::Relocate(format!(
+            "{} already exists",
+            from.display()
+        )));
+    }
+    if let Some(parent) = from.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    if let Err(err) = move_dir(&relocation.to, from) {
+        if linked {
+            let _ = make_symlink(&relocation.to, from);
+        }
+        return Err(err);
+    }
+    if let Some(dir) = config.dir() {
+        update_path_map(&dir, |map| map.retain(|entry| entry != relocation))?;
+    }
+    Ok(())
+}
+
+/// Renames `from` to `to`. Across filesystems, copies it instead, compares
+/// the copy with the original, contents included, and only then deletes the
+/// original.
+fn move_dir(from: &Path, to: &Path) -> Result<()> {
+    match fs::rename(from, to) {
+        Ok(()) => return Ok(()),
+        Err(err) if err.kind() == io::ErrorKind::CrossesDevices => {}
+        Err(err) => return Err(err.into()),
+    }
+    copy_dir(from, to, same_tree)
+}
+
+/// Copies `from` to `to`, checks the copy with `verify` and only then
+/// deletes `from`. A failed or differing copy is removed again.
+fn copy_dir(from: &Path, to: &Path, verify: fn(&Path, &Path) -> io::Result<bool>) -> Result<()> {
+    let copied = copy_tree(from, to).and_then(|_| {
+        if verify(from, to)? {
+            Ok(())
+        } else {
+            Err(io::Error::other(format!(
+                "copy of {} differs from the original",
+                from.display()
+            )))
+        }
+    });
+    if let Err(err) = copied {
+        let _ = remove_path(to);
+        return Err(err.into());
+    }
+    remove_path(from)
+}
+
+fn copy_tree(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let (source, dest) = (entry.path(), to.join(entry.file_name()));
+        let file_type = entry.file_type()?;
+        if file_type.is_symlink() {
+            make_symlink(&fs::read_link(&source)?, &dest)?;
+        } else if file_type.is_dir() {
+            copy_tree(&source, &dest)?;
+        } else {
+            fs::copy(&source, &dest)?;
+            let modified = entry.metadata()?.modified()?;
+            File::options()
+                .write(true)
+                .open(&dest)?
+                .set_modified(modified)?;
+        }
+    }
+    fs::set_permissions(to, fs::metadata(from)?.permissions())
+}
+
+type TreeEntry = (PathBuf, char, u64, Option<SystemTime>);
+
+/// Every entry below `dir` with its kind and, for files, size and
+/// modification time, sorted by path.
+fn tree(dir: &Path) -> io::Result<Vec<TreeEntry>> {
+    fn walk(dir: &Path, prefix: &Path, entries: &mut Vec<TreeEntry>) -> io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = prefix.join(entry.file_name());
+            let file_type = entry.file_type()?;
+            if file_type.is_symlink() {
+                entries.push((path, 'l', 0, None));
+            } else if file_type.is_dir() {
+                walk(&entry.path(), &path, entries)?;
+                entries.push((path, 'd', 0, None));
+            } else {
+                let metadata = entry.metadata()?;
+                entries.push((path, 'f', metadata.len(), metadata.modified().ok()));
+            }
+        }
+        Ok(())
+    }
+    let mut entries = Vec::new();
+    walk(dir, Path::new(""), &mut entries)?;
+    entries.sort();
+    Ok(entries)
+}
+
+/// Whether `copy` has the same entries as `dir`, with files of the same
+/// size, modification time and content.
+fn same_tree(dir: &Path, copy: &Path) -> io::Result<bool> {
+    let entries = tree(dir)?;
+    if entries != tree(copy)? {
+        return Ok(false);
+    }
+    for (path, ..) in entries.iter().filter(|(_, kind, ..)| *kind == 'f') {
+        if !same_content(&dir.join(path), &copy.join(path))? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+fn same_content(a: &Path, b: &Path) -> io::Result<bool> {
+    let (mut a, mut b) = (File::open(a)?, File::open(b)?);
+    let (mut buf_a, mut buf_b) = (vec![0; 64 * 1024], vec![0; 64 * 1024]);
+    loop {
+        let read = a.read(&mut buf_a)?;
+        if read == 0 {
+            return Ok(b.read(&mut buf_b)? == 0);
+        }
+        b.read_exact(&mut buf_b[..read])?;
+        if buf_a[..read] != buf_b[..read] {
+            return Ok(false);
+        }
+    }
+}
+
+fn read_path_map(dir: &Path) -> Result<Vec<Relocation>> {
+    match fs::read(dir.join(PATH_MAP)) {
+        Ok(json) => serde_json::from_slice(&json).map_err(|err| Error::Relocate(err.to_string())),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn update_path_map(dir: &Path, update: impl FnOnce(&mut Vec<Relocation>)) -> Result<()> {
+    let _guard = PATH_MAP_LOCK
+        .lock()
+        .map_err(|err| Error::LockError(err.to_string()))?;
+    let mut map = read_path_map(dir)?;
+    update(&mut map);
+    let json = serde_json::to_vec_pretty(&map).map_err(|err| Error::Relocate(err.to_string()))?;
+    let partial = dir.join(format!("{PATH_MAP}.partial"));
+    fs::write(&partial, json)?;
+    fs::rename(partial, dir.join(PATH_MAP))?;
+    Ok(())
+}
+
+#[cfg(unix)]
+fn make_symlink(target: &Path, link: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(windows)]
+fn make_symlink(target: &Path, link: &Path) -> io::Result<()> {
+    if target.is_dir() {
+        std::os::windows::fs::symlink_dir(target, link)
+    } else {
+        std::os::windows::fs::symlink_file(target, link)
+    }
+}
+
+fn remove_symlink(link: &Path) -> io::Result<()> {
+    // Directory symlinks are directories on Windows.
+    fs::remove_file(link).or_else(|_| fs::remove_dir(link))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn source() -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("vaporz-relocate-{}", Uuid::new_v4().simple()));
+        let dir = root.join("target");
+        fs::create_dir_all(dir.join("debug")).unwrap();
+        fs::write(dir.join("debug/app"), "binary").unwrap();
+        fs::write(dir.join("CACHEDIR.TAG"), "Signature").unwrap();
+        make_symlink(Path::new("debug/app"), &dir.join("app")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn copies_and_verifies_trees() {
+        let from = source();
+        let to = from.with_file_name("copy");
+        copy_dir(&from, &to, same_tree).unwrap();
+
+        assert!(!from.exists());
+        assert_eq!(fs::read_to_string(to.join("debug/app")).unwrap(), "binary");
+        assert_eq!(
+            fs::read_link(to.join("app")).unwrap(),
+            Path::new("debug/app")
+        );
+
+        fs::remove_dir_all(to.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn keeps_the_source_when_the_copy_differs() {
+        let from = source();
+        let to = from.with_file_name("copy");
+        // Same size and modification time, only the content differs.
+        let corrupt = |from: &Path, to: &Path| {
+            let file = to.join("debug/app");
+            let modified = fs::metadata(&file)?.modified()?;
+            fs::write(&file, "BINARY")?;
+            File::options()
+                .write(true)
+                .open(&file)?
+                .set_modified(modified)?;
+            same_tree(from, to)
+        };
+        assert!(copy_dir(&from, &to, corrupt).is_err());
+
+        assert!(!to.exists());
+        assert_eq!(
+            fs::read_to_string(from.join("debug/app")).unwrap(),
+            "binary"
+        );
+        assert_eq!(tree(&from).unwrap().len(), 4);
+
+        fs::remove_dir_all(from.parent().unwrap()).unwrap();
+    }
+}
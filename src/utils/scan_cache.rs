@@ -0,0 +1,171 @@
+use crate::{
+    config::AgeSource,
+    errors::Result,
+    models::{FolderInfo, ProcessStatus},
+    utils::fs::{DirSize, modified},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// Modification times of an artifact and of the directory it was found in,
+/// taken when it was scanned. A cached row is kept on screen while both
+/// match, until the folder is sized again.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Fingerprint {
+    pub artifact: Option<SystemTime>,
+    pub project: Option<SystemTime>,
+}
+
+impl Fingerprint {
+    pub fn read(artifact: &Path, project: &Path) -> Self {
+        Self {
+            artifact: modified(artifact),
+            project: modified(project),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct CachedFolder {
+    path: PathBuf,
+    target: String,
+    details: Option<String>,
+    project: PathBuf,
+    workspace: Option<PathBuf>,
+    fingerprint: Fingerprint,
+    #[serde(default)]
+    mount: Option<PathBuf>,
+    apparent: u64,
+    allocated: u64,
+    exclusive: u64,
+    errors: u64,
+    newest_modified: Option<SystemTime>,
+    last_modified: SystemTime,
+}
+
+impl CachedFolder {
+    fn new(folder: &FolderInfo) -> Option<Self> {
+        let size = folder.dir_size()?;
+        Some(Self {
+            path: folder.path.clone(),
+            target: folder.target.clone(),
+            details: folder.details.clone(),
+            project: folder.project.clone(),
+            workspace: folder.workspace.clone(),
+            fingerprint: folder.fingerprint,
+            mount: folder.mount.clone(),
+            apparent: size.apparent,
+            allocated: size.allocated,
+            exclusive: size.exclusive,
+            errors: size.errors,
+            newest_modified: size.newest_modified,
+            last_modified: folder.last_modified()?,
+        })
+    }
+
+    fn folder(&self) -> FolderInfo {
+        let folder = FolderInfo::new(self.path.clone())
+            .target(&self.target)
+            .details(self.details.clone())
+            .project(self.project.clone())
+            .workspace(self.workspace.clone())
+            .fingerprint(self.fingerprint)
+            .mount(self.mount.clone());
+        folder.set_size(DirSize::restored(
+            self.apparent,
+            self.allocated,
+            self.exclusive,
+            self.errors,
+            self.newest_modified,
+        ));
+        // Git activity is not cached: local work that appeared since the last
+        // run must show up, it is what protects a folder from removal.
+        folder.set_last_modified(self.last_modified);
+        folder
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct CacheFile {
+    root: PathBuf,
+    /// What the cached modification times measure.
+    #[serde(default)]
+    age: Option<AgeSource>,
+    folders: Vec<CachedFolder>,
+}
+
+/// Scan results of one root directory, persisted between runs. A cache
+/// written with another `sizing.age` is discarded.
+#[derive(Default)]
+pub struct ScanCache {
+    folders: HashMap<PathBuf, CachedFolder>,
+}
+
+impl ScanCache {
+    pub fn load(root: &Path, age: AgeSource) -> Self {
+        let folders = cache_file(root)
+            .and_then(|file| fs::read_to_string(file).ok())
+            .and_then(|content| serde_json::from_str::<CacheFile>(&content).ok())
+            .filter(|cache| cache.root == root && cache.age == Some(age))
+            .map(|cache| cache.folders)
+            .unwrap_or_default();
+        Self {
+            folders: folders
+                .into_iter()
+                .map(|folder| (folder.path.clone(), folder))
+                .collect(),
+        }
+    }
+
+    pub fn save(root: &Path, age: AgeSource, rows: &[FolderInfo]) -> Result<()> {
+        let Some(file) = cache_file(root) else {
+            return Ok(());
+        };
+        let cache = CacheFile {
+            root: root.to_path_buf(),
+            age: Some(age),
+            folders: rows
+                .iter()
+                .filter(|row| row.removal_status == ProcessStatus::Pending)
+                .filter_map(CachedFolder::new)
+                .collect(),
+        };
+        if let Some(dir) = file.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let content = serde_json::to_string(&cache).map_err(std::io::Error::from)?;
+        fs::write(file, content)?;
+        Ok(())
+    }
+
+    pub fn rows(&self) -> Vec<FolderInfo> {
+        self.folders.values().map(CachedFolder::folder).collect()
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.folders.keys().map(PathBuf::as_path)
+    }
+
+    /// Whether the cached entry for a freshly scanned folder is still valid,
+    /// or `None` if the folder is not cached.
+    pub fn is_valid(&self, folder: &FolderInfo) -> Option<bool> {
+        let cached = self.folders.get(&folder.path)?;
+        Some(cached.fingerprint == folder.fingerprint && folder.fingerprint.artifact.is_some())
+    }
+}
+
+fn cache_file(root: &Path) -> Option<PathBuf> {
+    let app_name = env!("CARGO_PKG_NAME");
+    let hash = root
+        .to_string_lossy()
+        .bytes()
+        .fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+    dirs::cache_dir().map(|dir| dir.join(format!("{app_name}/scans/{hash:016x}.json")))
+}
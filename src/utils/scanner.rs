@@ -1,67 +1,261 @@
-use crate::config;
-use crate::models::{FolderInfo, TargetInfo};
-use std::path::Path;
+use crate::config::{Config, ScanConfig};
+use crate::detector::{Candidate, Detector, Directory, default_detectors};
+use crate::models::{CacheInfo, FolderInfo};
+use crate::utils::{
+    mounts::{device, mount_point},
+    scan_cache::Fingerprint,
+    workspace::is_workspace_root,
+};
+use rayon::prelude::*;
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
 use tokio::sync::mpsc::UnboundedSender;
-use walkdir::WalkDir;
 
-pub fn find_target_dirs<P: AsRef<Path>>(
+pub fn find_target_dirs<P: AsRef<Path>>(dir: P, config: Config, tx: UnboundedSender<FolderInfo>) {
+    let detectors = default_detectors(config.targets);
+    find_artifacts(dir, &config.scan, &detectors, tx);
+}
+
+pub fn find_artifacts<P: AsRef<Path>>(
     dir: P,
-    targets: Vec<TargetInfo>,
+    scan: &ScanConfig,
+    detectors: &[Box<dyn Detector>],
     tx: UnboundedSender<FolderInfo>,
 ) {
-    let mut walker = WalkDir::new(dir).into_iter();
+    let started = Instant::now();
+    let scanner = Scanner {
+        scan,
+        detectors,
+        tx,
+        recursive: true,
+        device: root_device(dir.as_ref(), scan),
+        found: AtomicUsize::new(0),
+    };
+    scanner.visit(dir.as_ref(), false, None, &[], &[]);
+    log::info!(
+        root:% = dir.as_ref().display(),
+        found = scanner.found.load(Ordering::Relaxed),
+        elapsed_ms = started.elapsed().as_millis() as u64;
+        "scan finished"
+    );
+}
+
+/// Like [`find_artifacts`], but only looks at `dir` itself without
+/// descending into its subdirectories.
+pub fn find_artifacts_in<P: AsRef<Path>>(
+    dir: P,
+    scan: &ScanConfig,
+    detectors: &[Box<dyn Detector>],
+    tx: UnboundedSender<FolderInfo>,
+) {
+    let scanner = Scanner {
+        scan,
+        detectors,
+        tx,
+        recursive: false,
+        device: root_device(dir.as_ref(), scan),
+        found: AtomicUsize::new(0),
+    };
+    scanner.visit(dir.as_ref(), false, None, &[], &[]);
+}
+
+struct Scanner<'a> {
+    scan: &'a ScanConfig,
+    detectors: &'a [Box<dyn Detector>],
+    tx: UnboundedSender<FolderInfo>,
+    recursive: bool,
+    /// The device the scan stays on, with `one_file_system`.
+    device: Option<u64>,
+    /// Artifacts sent so far.
+    found: AtomicUsize,
+}
 
-    while let Some(Ok(entry)) = walker.next() {
-        let path = entry.path();
+fn root_device(dir: &Path, scan: &ScanConfig) -> Option<u64> {
+    scan.one_file_system.then(|| device(dir)).flatten()
+}
 
-        if !entry.file_type().is_dir() || entry.path_is_symlink() {
-            continue;
+impl Scanner<'_> {
+    /// `projects` are the project roots above `path` where a detector found
+    /// artifacts, by index of that detector, and are searched for nested
+    /// ones.
+    fn visit(
+        &self,
+        path: &Path,
+        hidden: bool,
+        workspace: Option<&Path>,
+        claimed: &[PathBuf],
+        projects: &[(usize, PathBuf)],
+    ) {
+        let dir = Directory::read(path);
+        let detected = self.detect(&dir);
+
+        if let Some((_, candidate)) = detected.iter().find(|(_, c)| c.path == path) {
+            self.send(candidate.clone(), None, workspace);
+            return;
         }
 
-        if let Some(name) = path.file_name().and_then(|n| n.to_str())
-            && name.starts_with('.')
-        {
-            walker.skip_current_dir();
-            continue;
+        if hidden {
+            return;
         }
 
-        if let Some(target) = targets.iter().find(|t| t.is_project_root(path)) {
-            find_artifacts_in_project(path, target, &mut walker, &tx);
+        let workspace = match workspace {
+            Some(workspace) => Some(workspace),
+            None => (!detected.is_empty() && is_workspace_root(&dir)).then_some(path),
+        };
+
+        let mut claimed = claimed
+            .iter()
+            .filter(|claimed| claimed.starts_with(path))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        // A project root found here takes over from one of the same detector
+        // further up.
+        let mut projects = projects.to_vec();
+        for (index, _) in &detected {
+            projects.retain(|(other, _)| other != index);
+            projects.push((*index, path.to_path_buf()));
         }
-    }
-}
 
-fn find_artifacts_in_project(
-    project_root: &Path,
-    target: &TargetInfo,
-    walker: &mut walkdir::IntoIter,
-    tx: &UnboundedSender<FolderInfo>,
-) {
-    let mut subwalker = WalkDir::new(project_root).into_iter();
-
-    while let Some(Ok(entry)) = subwalker.next() {
-        if let Some(name) = entry.file_name().to_str()
-            && target.artifacts.iter().any(|artifact| artifact == name)
-        {
-            let info = FolderInfo::new(entry.path().to_path_buf());
-            let _ = tx.send(info);
-            subwalker.skip_current_dir();
+        let nested = projects
+            .iter()
+            .filter(|(_, project)| project != path)
+            .flat_map(|(index, project)| {
+                self.detectors[*index]
+                    .detect_nested(&dir)
+                    .into_iter()
+                    .map(move |candidate| (project.as_path(), candidate))
+            })
+            .filter(|(_, candidate)| detected.iter().all(|(_, c)| c.path != candidate.path))
+            .collect::<Vec<_>>();
+        let candidates = detected
+            .into_iter()
+            .map(|(_, candidate)| (path, candidate))
+            .chain(nested);
+
+        for (project, mut candidate) in candidates {
+            if !self.on_device(&candidate.path) || claimed.contains(&candidate.path) {
+                continue;
+            }
+            if candidate.path.is_dir() {
+                if candidate.details.is_none() {
+                    candidate.details = self.details(&candidate.path);
+                }
+                claimed.push(candidate.path.clone());
+            }
+            self.send(candidate, Some(project), workspace);
+        }
+
+        if !self.recursive {
+            return;
         }
+
+        let subdirs = dir
+            .subdirs()
+            .map(|entry| (path.join(&entry.name), &entry.name))
+            .filter(|(subdir, _)| !claimed.contains(subdir))
+            .collect::<Vec<_>>();
+
+        subdirs.into_par_iter().for_each(|(subdir, name)| {
+            if !self.on_device(&subdir) {
+                return;
+            }
+            let hidden = name
+                .to_str()
+                .is_some_and(|name| name.starts_with('.') && self.scan.skips_hidden(name));
+            self.visit(&subdir, hidden, workspace, &claimed, &projects);
+        });
+    }
+
+    fn on_device(&self, path: &Path) -> bool {
+        self.device.is_none_or(|root| device(path) == Some(root))
+    }
+
+    /// Candidates of every detector, with the index of the one that found
+    /// them.
+    fn detect(&self, dir: &Directory) -> Vec<(usize, Candidate)> {
+        self.detectors
+            .iter()
+            .enumerate()
+            .flat_map(|(index, detector)| {
+                detector
+                    .detect(dir)
+                    .into_iter()
+                    .map(move |candidate| (index, candidate))
+            })
+            .collect()
     }
 
-    walker.skip_current_dir();
+    fn details(&self, path: &Path) -> Option<String> {
+        self.detect(&Directory::read(path))
+            .into_iter()
+            .find(|(_, candidate)| candidate.path == path)
+            .and_then(|(_, candidate)| candidate.details)
+    }
+
+    fn send(&self, candidate: Candidate, project: Option<&Path>, workspace: Option<&Path>) {
+        let mut info = FolderInfo::new(candidate.path);
+        if let Some(project) = project {
+            info = info.project(project.to_path_buf());
+        }
+        let fingerprint = Fingerprint::read(&info.path, &info.project);
+        let mount = mount_point(&info.path);
+        let info = info
+            .fingerprint(fingerprint)
+            .mount(mount)
+            .target(&candidate.target)
+            .details(candidate.details)
+            .workspace(workspace.map(Path::to_path_buf));
+        log::debug!(path:% = info.path.display(), ecosystem = info.target.as_str(); "found artifact");
+        self.found.fetch_add(1, Ordering::Relaxed);
+        let _ = self.tx.send(info);
+    }
 }
 
-pub fn scan_current_dir(tx: UnboundedSender<FolderInfo>) {
-    if let Ok(root) = std::env::current_dir() {
-        let targets = get_targets();
-        find_target_dirs(root, targets, tx);
+pub fn find_global_caches(caches: &[CacheInfo], tx: UnboundedSender<FolderInfo>) {
+    for cache in caches {
+        if let Some(path) = cache.locate() {
+            let mount = mount_point(&path);
+            let _ = tx.send(FolderInfo::new(path).target(&cache.name).mount(mount));
+        }
     }
 }
 
-fn get_targets() -> Vec<TargetInfo> {
-    if let Ok(config) = config::load_config() {
-        return config.targets;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tokio::sync::mpsc;
+    use uuid::Uuid;
+
+    #[test]
+    fn finds_artifacts_nested_in_a_project() {
+        let root = std::env::temp_dir().join(format!("vaporz-scan-{}", Uuid::new_v4().simple()));
+        fs::create_dir_all(root.join("web/node_modules/react")).unwrap();
+        fs::create_dir_all(root.join("web/scripts/legacy/node_modules/jquery")).unwrap();
+        fs::write(root.join("web/package.json"), "{}").unwrap();
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        find_target_dirs(&root, Config::default(), tx);
+        let mut found = Vec::new();
+        while let Ok(folder) = rx.try_recv() {
+            found.push((folder.path, folder.project));
+        }
+        found.sort();
+
+        assert_eq!(
+            found,
+            [
+                (root.join("web/node_modules"), root.join("web")),
+                (
+                    root.join("web/scripts/legacy/node_modules"),
+                    root.join("web")
+                ),
+            ]
+        );
+        fs::remove_dir_all(&root).unwrap();
     }
-    Vec::new()
 }
@@ -0,0 +1,134 @@
+use crate::{config::SizingConfig, enricher::Enricher, models::FolderInfo};
+use std::{
+    collections::HashSet,
+    fs,
+    sync::{Arc, Condvar, Mutex, mpsc},
+    thread,
+};
+use uuid::Uuid;
+
+struct Job {
+    folder: FolderInfo,
+    /// Estimate of how much the folder holds, bigger ones go first.
+    weight: u64,
+}
+
+#[derive(Default)]
+struct Queue {
+    jobs: Vec<Job>,
+    visible: HashSet<Uuid>,
+}
+
+impl Queue {
+    fn next(&mut self, lazy: bool) -> Option<Job> {
+        let index = self
+            .jobs
+            .iter()
+            .enumerate()
+            .filter(|(_, job)| !lazy || self.visible.contains(&job.folder.id))
+            .max_by_key(|(_, job)| (self.visible.contains(&job.folder.id), job.weight))
+            .map(|(index, _)| index)?;
+        Some(self.jobs.swap_remove(index))
+    }
+}
+
+struct Shared {
+    queue: Mutex<Queue>,
+    ready: Condvar,
+    lazy: bool,
+    enricher: Box<dyn Enricher>,
+}
+
+impl Shared {
+    /// Queues a job, dropping any queued for a folder at the same path: the
+    /// row it was for has been replaced.
+    fn push(&self, job: Job) {
+        if let Ok(mut queue) = self.queue.lock() {
+            queue
+                .jobs
+                .retain(|queued| queued.folder.path != job.folder.path);
+            queue.jobs.push(job);
+            self.ready.notify_one();
+        }
+    }
+}
+
+/// Computes size and last-modified time of folders on a fixed number of
+/// worker threads, rows on screen first and then the ones with the most
+/// entries.
+#[derive(Clone)]
+pub struct SizeScheduler {
+    shared: Arc<Shared>,
+    /// Folders to weigh before they are queued, on a thread of their own so
+    /// scheduling never waits on the disk.
+    unweighed: mpsc::Sender<FolderInfo>,
+}
+
+impl SizeScheduler {
+    pub fn new(config: SizingConfig, enricher: impl Enricher + 'static) -> Self {
+        let shared = Arc::new(Shared {
+            queue: Mutex::new(Queue::default()),
+            ready: Condvar::new(),
+            lazy: config.lazy,
+            enricher: Box::new(enricher),
+        });
+
+        for _ in 0..config.workers.max(1) {
+            let shared = Arc::clone(&shared);
+            thread::spawn(move || Self::work(&shared));
+        }
+
+        let (unweighed, unweighed_rx) = mpsc::channel();
+        {
+            let shared = Arc::clone(&shared);
+            thread::spawn(move || Self::weigh(&shared, unweighed_rx));
+        }
+
+        Self { shared, unweighed }
+    }
+
+    pub fn schedule(&self, folder: &FolderInfo) {
+        if folder.is_enriched() {
+            return;
+        }
+        let _ = self.unweighed.send(folder.clone());
+    }
+
+    fn weigh(shared: &Shared, unweighed: mpsc::Receiver<FolderInfo>) {
+        for folder in unweighed {
+            // Only the top level is counted: a `node_modules` with a thousand
+            // packages outweighs a `target` with three profiles either way.
+            let weight = fs::read_dir(&folder.path)
+                .map(|entries| entries.count() as u64)
+                .unwrap_or_default();
+            shared.push(Job { folder, weight });
+        }
+    }
+
+    pub fn prioritize(&self, visible: impl IntoIterator<Item = Uuid>) {
+        if let Ok(mut queue) = self.shared.queue.lock() {
+            queue.visible = visible.into_iter().collect();
+            self.shared.ready.notify_all();
+        }
+    }
+
+    fn work(shared: &Shared) {
+        loop {
+            let job = {
+                let Ok(mut queue) = shared.queue.lock() else {
+                    return;
+                };
+                loop {
+                    if let Some(job) = queue.next(shared.lazy) {
+                        break job;
+                    }
+                    queue = match shared.ready.wait(queue) {
+                        Ok(queue) => queue,
+                        Err(_) => return,
+                    };
+                }
+            };
+            shared.enricher.enrich(&job.folder);
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use crate::detector::Directory;
+use std::{fs, path::Path};
+
+/// A venv, virtualenv or conda environment. The base install of conda
+/// (`~/miniconda3`, `~/anaconda3`) has a `conda-meta` too but is not an
+/// environment to throw away: it holds `condabin` and the package cache.
+pub fn is_virtualenv(dir: &Directory) -> bool {
+    let is_dir = |name| dir.get(name).is_some_and(|entry| entry.file_type.is_dir());
+    dir.get("pyvenv.cfg")
+        .is_some_and(|entry| entry.file_type.is_file())
+        || (is_dir("conda-meta") && !is_dir("condabin") && !is_dir("pkgs"))
+}
+
+pub fn python_version(dir: &Path) -> Option<String> {
+    pyvenv_version(dir).or_else(|| conda_version(dir))
+}
+
+fn pyvenv_version(dir: &Path) -> Option<String> {
+    let content = fs::read_to_string(dir.join("pyvenv.cfg")).ok()?;
+    let entries = content
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim(), value.trim()))
+        .collect::<Vec<_>>();
+
+    ["version", "version_info"].iter().find_map(|wanted| {
+        entries
+            .iter()
+            .find(|(key, _)| key == wanted)
+            .map(|(_, value)| value.splitn(4, '.').take(3).collect::<Vec<_>>().join("."))
+    })
+}
+
+fn conda_version(dir: &Path) -> Option<String> {
+    fs::read_dir(dir.join("conda-meta"))
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .find_map(|name| {
+            let version = name.strip_prefix("python-")?.split('-').next()?;
+            version
+                .starts_with(|c: char| c.is_ascii_digit())
+                .then(|| version.to_string())
+        })
+}
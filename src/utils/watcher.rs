@@ -0,0 +1,123 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, mpsc::Sender},
+};
+
+/// Reports changes inside watched directories as the path of the entry that
+/// was created, written, moved or deleted.
+pub trait FileWatcher: Send + Sync {
+    /// Watches the direct entries of `dir`. Watching a directory twice is a no-op.
+    fn watch(&self, dir: &Path);
+}
+
+/// The watcher for the current platform, or `None` where live updates are
+/// not supported.
+pub fn platform_watcher(tx: Sender<PathBuf>) -> Option<Arc<dyn FileWatcher>> {
+    #[cfg(target_os = "linux")]
+    match inotify::InotifyWatcher::new(tx) {
+        Ok(watcher) => Some(Arc::new(watcher)),
+        Err(err) => {
+            log::warn!("failed to start inotify: {err}");
+            None
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = tx;
+        log::warn!("watch mode is not supported on this platform");
+        None
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod inotify {
+    use super::FileWatcher;
+    use inotify::{EventMask, Inotify, WatchMask, Watches};
+    use std::{
+        collections::{HashMap, HashSet},
+        io,
+        path::{Path, PathBuf},
+        sync::{Arc, Mutex, mpsc::Sender},
+        thread,
+    };
+
+    struct State {
+        watches: Watches,
+        dirs: HashMap<i32, PathBuf>,
+        watched: HashSet<PathBuf>,
+    }
+
+    pub struct InotifyWatcher {
+        state: Arc<Mutex<State>>,
+    }
+
+    impl InotifyWatcher {
+        pub fn new(tx: Sender<PathBuf>) -> io::Result<Self> {
+            let mut inotify = Inotify::init()?;
+            let state = Arc::new(Mutex::new(State {
+                watches: inotify.watches(),
+                dirs: HashMap::new(),
+                watched: HashSet::new(),
+            }));
+
+            let thread_state = state.clone();
+            thread::spawn(move || {
+                let mut buffer = [0; 4096];
+                while let Ok(events) = inotify.read_events_blocking(&mut buffer) {
+                    let Ok(mut state) = thread_state.lock() else {
+                        return;
+                    };
+                    for event in events {
+                        let id = event.wd.get_watch_descriptor_id();
+                        if event.mask.contains(EventMask::IGNORED) {
+                            if let Some(dir) = state.dirs.remove(&id) {
+                                state.watched.remove(&dir);
+                            }
+                            continue;
+                        }
+                        let Some(dir) = state.dirs.get(&id) else {
+                            continue;
+                        };
+                        let path = match event.name {
+                            Some(name) => dir.join(name),
+                            None => dir.clone(),
+                        };
+                        if tx.send(path).is_err() {
+                            return;
+                        }
+                    }
+                }
+            });
+
+            Ok(Self { state })
+        }
+    }
+
+    impl FileWatcher for InotifyWatcher {
+        fn watch(&self, dir: &Path) {
+            let Ok(mut state) = self.state.lock() else {
+                return;
+            };
+            if state.watched.contains(dir) {
+                return;
+            }
+            let mask = WatchMask::CREATE
+                | WatchMask::DELETE
+                | WatchMask::CLOSE_WRITE
+                | WatchMask::MOVED_FROM
+                | WatchMask::MOVED_TO
+                | WatchMask::DELETE_SELF
+                | WatchMask::MOVE_SELF
+                | WatchMask::ONLYDIR;
+            match state.watches.add(dir, mask) {
+                Ok(wd) => {
+                    state
+                        .dirs
+                        .insert(wd.get_watch_descriptor_id(), dir.to_path_buf());
+                    state.watched.insert(dir.to_path_buf());
+                }
+                Err(err) => log::debug!("failed to watch {}: {err}", dir.display()),
+            }
+        }
+    }
+}
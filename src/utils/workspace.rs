@@ -0,0 +1,23 @@
+use crate::detector::Directory;
+use std::{fs, path::Path};
+
+static WORKSPACE_FILES: [&str; 6] = [
+    "pnpm-workspace.yaml",
+    "lerna.json",
+    "nx.json",
+    "go.work",
+    "settings.gradle",
+    "settings.gradle.kts",
+];
+
+pub fn is_workspace_root(dir: &Directory) -> bool {
+    WORKSPACE_FILES.iter().any(|file| dir.contains(file))
+        || (dir.contains("Cargo.toml")
+            && file_contains(&dir.path().join("Cargo.toml"), "[workspace]"))
+        || (dir.contains("package.json")
+            && file_contains(&dir.path().join("package.json"), "\"workspaces\""))
+}
+
+fn file_contains(path: &Path, needle: &str) -> bool {
+    fs::read_to_string(path).is_ok_and(|content| content.contains(needle))
+}